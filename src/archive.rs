@@ -0,0 +1,447 @@
+use std::{
+    borrow::Cow, cell::{Cell, RefCell}, fs::File, io::{self, BufReader, Read, Seek, SeekFrom, Write}, path::Path
+};
+use compress::zlib;
+
+use crate::entry::{join_path, DirEntry, FileEntry, Header, HvpEntry, Walk};
+use crate::error::{HvpError, Result};
+use crate::index::Index;
+use crate::name::NameEncoding;
+use crate::writer::record_offset;
+
+pub(crate) static TAG: &[u8] = "HV PackFile".as_bytes();
+
+/// Settings for parsing an archive and reading its entries.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub encoding: NameEncoding,
+    /// The most bytes a single entry may hold once decompressed.
+    pub max_entry_size: u64,
+    /// The most bytes all entries read from the archive may add up to.
+    pub max_total_size: u64,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { encoding: NameEncoding::default(), max_entry_size: u64::MAX, max_total_size: u64::MAX }
+    }
+}
+
+pub struct HvpArchive<R = File> {
+    pub(crate) file: RefCell<R>,
+    pub(crate) len: u64,
+    pub(crate) header: Header,
+    pub(crate) entries: Vec<HvpEntry>,
+    pub(crate) index: Index,
+    pub(crate) options: ParseOptions,
+    /// The number of bytes handed out by `read` so far.
+    pub(crate) total_read: Cell<u64>,
+}
+
+impl HvpArchive<File> {
+    /// Opens `path` and parses its entry tree. No file data is read until
+    /// [`HvpArchive::read`] is called.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<HvpArchive<File>> {
+        HvpArchive::open_with(path, &ParseOptions::default())
+    }
+
+    pub fn open_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<HvpArchive<File>> {
+        HvpArchive::new_with(File::open(path)?, options)
+    }
+
+    /// Opens `path` for reading and writing, to edit it in place.
+    pub fn open_rw<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<HvpArchive<File>> {
+        HvpArchive::new_with(File::options().read(true).write(true).open(path)?, options)
+    }
+}
+
+impl<R> HvpArchive<R> {
+    pub(crate) fn from_parts(file: R, len: u64, header: Header, entries: Vec<HvpEntry>, options: &ParseOptions) -> HvpArchive<R> {
+        HvpArchive {
+            file: RefCell::new(file),
+            len,
+            header,
+            index: Index::new(&entries),
+            entries,
+            options: options.clone(),
+            total_read: Cell::new(0),
+        }
+    }
+}
+
+impl<R: Read + Seek> HvpArchive<R> {
+    /// Parses the entry tree of an archive held by any seekable reader, such
+    /// as a `Cursor<Vec<u8>>` over an archive already in memory.
+    pub fn new(file: R) -> Result<HvpArchive<R>> {
+        HvpArchive::new_with(file, &ParseOptions::default())
+    }
+
+    pub fn new_with(mut file: R, options: &ParseOptions) -> Result<HvpArchive<R>> {
+        let len = file.seek(SeekFrom::End(0))?;
+        file.rewind()?;
+        let (header, entries) = parse(&mut file, len, options)?;
+        Ok(HvpArchive::from_parts(file, len, header, entries, options))
+    }
+
+    /// The length of the whole archive in bytes.
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The top-level entries of the archive.
+    pub fn entries(&self) -> &[HvpEntry] {
+        &self.entries
+    }
+
+    /// Every entry of the archive, depth-first.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk::new(&self.entries)
+    }
+
+    /// Every file entry of the archive, depth-first.
+    pub fn files(&self) -> impl Iterator<Item = &FileEntry> {
+        self.walk().filter_map(|entry| match entry {
+            HvpEntry::File(file) => Some(file),
+            HvpEntry::Directory(_) => None,
+        })
+    }
+
+    /// The file entry at `path`, components separated by `/`.
+    pub fn find(&self, path: &str) -> Option<&FileEntry> {
+        match self.get(path)? {
+            HvpEntry::File(file) => Some(file),
+            HvpEntry::Directory(_) => None,
+        }
+    }
+
+    /// The position of the record of the entry at `path` in the archive.
+    pub fn record_offset(&self, path: &str) -> Option<u64> {
+        record_offset(&self.entries, path)
+    }
+
+    /// Reads the contents of `entry`, decompressing them if needed. Fails
+    /// without reading anything if `entry` would exceed the size limits the
+    /// archive was opened with.
+    pub fn read(&self, entry: &FileEntry) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.read_to(entry, &mut data)?;
+        Ok(data)
+    }
+
+    /// Writes the contents of `entry` to `out` while they are decompressed,
+    /// returning the number of bytes written. Checks the same limits as
+    /// [`HvpArchive::read`].
+    pub fn read_to<W: Write>(&self, entry: &FileEntry, out: &mut W) -> Result<u64> {
+        self.check_bounds(entry)?;
+        check_limits(&self.options, self.total_read.get(), entry)?;
+        let file = &mut *self.file.borrow_mut();
+        file.seek(SeekFrom::Start(entry.offset.into()))?;
+        let written = copy_entry(file, entry, out)?;
+        self.total_read.set(self.total_read.get() + written);
+        Ok(written)
+    }
+
+    /// Reads the data of `entry` exactly as stored in the archive, without
+    /// decompressing it.
+    pub fn read_raw(&self, entry: &FileEntry) -> Result<Vec<u8>> {
+        self.check_bounds(entry)?;
+        self.read_at(entry.offset.into(), entry.stored_len() as usize)
+    }
+
+    /// Reads `len` bytes at `offset` from the start of the archive.
+    pub fn read_at(&self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        if offset + len as u64 > self.len {
+            return Err(HvpError::Truncated { offset: self.len });
+        }
+        let file = &mut *self.file.borrow_mut();
+        file.seek(SeekFrom::Start(offset))?;
+        read_bytes(file, len)
+    }
+
+    /// Checks that the stored data of `entry` lies inside the archive.
+    pub(crate) fn check_bounds(&self, entry: &FileEntry) -> Result<()> {
+        check_bounds(self.len, entry)
+    }
+}
+
+/// Checks that the stored data of `entry` lies inside an archive of `len` bytes.
+pub(crate) fn check_bounds(len: u64, entry: &FileEntry) -> Result<()> {
+    let stored = entry.stored_len();
+    if u64::from(entry.offset) + u64::from(stored) > len {
+        return Err(HvpError::OutOfBounds { entry: entry.path.clone(), offset: entry.offset.into(), len: stored.into() });
+    }
+    Ok(())
+}
+
+/// Checks that reading `entry` after `total` bytes stays within the size limits.
+pub(crate) fn check_limits(options: &ParseOptions, total: u64, entry: &FileEntry) -> Result<()> {
+    let size = u64::from(entry.size);
+    if size > options.max_entry_size {
+        return Err(HvpError::EntryTooLarge { entry: entry.path.clone(), limit: options.max_entry_size });
+    }
+    if total + size > options.max_total_size {
+        return Err(HvpError::TotalTooLarge { limit: options.max_total_size });
+    }
+    Ok(())
+}
+
+/// Writes the contents of `entry` to `out`, reading its stored data from
+/// `stored`, which must be positioned at the start of it. The data is read,
+/// inflated and written a chunk at a time.
+pub(crate) fn copy_entry<S: Read, W: Write>(stored: S, entry: &FileEntry, out: &mut W) -> Result<u64> {
+    if entry.compressed {
+        inflate_to(BufReader::new(stored.take(entry.comp_size.into())), entry, out)
+    } else {
+        Ok(io::copy(&mut stored.take(entry.size.into()), out)?)
+    }
+}
+
+// Names longer than this are taken to be garbage.
+const MAX_NAME_LEN: u32 = 1024;
+// The shortest possible entry record, a directory with an empty name.
+const MIN_RECORD_LEN: u64 = 17;
+// Directories nested deeper than this are taken to be garbage.
+pub(crate) const MAX_DEPTH: usize = 256;
+
+/// Where the header and entry records are parsed from.
+pub(crate) trait Source {
+    /// The offset of the next byte to read.
+    fn position(&mut self) -> Result<u64>;
+    fn read_integer(&mut self) -> Result<u32>;
+    fn read_one(&mut self) -> Result<u8>;
+    /// The next `len` bytes, borrowed if the source holds them in memory.
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'_, [u8]>>;
+
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<()> {
+        buf.copy_from_slice(&self.read_bytes(buf.len())?);
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Source for &mut R {
+    fn position(&mut self) -> Result<u64> {
+        Ok(self.stream_position()?)
+    }
+
+    fn read_integer(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(read_four(*self)?))
+    }
+
+    fn read_one(&mut self) -> Result<u8> {
+        let mut buf = [0];
+        read_into(*self, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'_, [u8]>> {
+        Ok(Cow::Owned(read_bytes(*self, len)?))
+    }
+
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<()> {
+        read_into(*self, buf)
+    }
+}
+
+/// Parses the header and the entry tree of an archive of `len` bytes.
+pub(crate) fn parse<S: Source>(mut source: S, len: u64, options: &ParseOptions) -> Result<(Header, Vec<HvpEntry>)> {
+    let tag = source.read_bytes(TAG.len()).map_err(|e| match e {
+        HvpError::Truncated { .. } => HvpError::BadMagic,
+        e => e,
+    })?;
+    if *tag != *TAG {
+        return Err(HvpError::BadMagic);
+    }
+    let mut header = Header::default();
+    source.read_into(&mut header.unknown1)?;
+    let count_offset = source.position()?;
+    let n = source.read_integer()?;
+    source.read_into(&mut header.unknown2)?;
+    let mut parser = Parser { source, len, options, depth: 0, pending: 0 };
+    parser.check_count(n, count_offset)?;
+    let mut entries = Vec::new();
+    for _ in 0..n {
+        entries.push(parser.read_next("")?);
+    }
+    Ok((header, entries))
+}
+
+struct Parser<'a, S> {
+    source: S,
+    /// The length of the whole archive.
+    len: u64,
+    options: &'a ParseOptions,
+    /// The number of directories the next record is nested in.
+    depth: usize,
+    /// The number of records announced by the header and the directories
+    /// read so far that have not been read yet.
+    pending: u64,
+}
+
+impl<S: Source> Parser<'_, S> {
+    // 4 - ???
+    // 1 - 0 -> directory, file otherwise
+    fn read_next(&mut self, parent: &str) -> Result<HvpEntry> {
+        let record = self.source.position()?;
+        self.pending -= 1;
+        let prefix = self.source.read_integer()?;
+        let file_type = self.source.read_one()?;
+        if file_type != 0 {
+            Ok(HvpEntry::File(self.read_file(parent, prefix, file_type, record)?))
+        } else {
+            Ok(HvpEntry::Directory(self.read_directory(parent, prefix, record)?))
+        }
+    }
+
+    // 4 - ???
+    // 4 - no of files
+    // 4 - length of the name
+    // x - the name
+    fn read_directory(&mut self, parent: &str, prefix: u32, record: u64) -> Result<DirEntry> {
+        let unknown = self.source.read_integer()?;
+        let no_of_files = self.source.read_integer()?;
+        let (name, raw_name) = self.read_name(record)?;
+        self.check_count(no_of_files, record)?;
+        if no_of_files > 0 && self.depth == MAX_DEPTH {
+            return Err(bad_record(record, format!("directories are nested deeper than {} levels", MAX_DEPTH)));
+        }
+        let path = join_path(parent, &name);
+        let mut children = Vec::new();
+        self.depth += 1;
+        for _ in 0..no_of_files {
+            children.push(self.read_next(&path)?);
+        }
+        self.depth -= 1;
+        Ok(DirEntry { name, raw_name, path, prefix, unknown, children })
+    }
+
+    // 4 - 1 -> is compressed
+    // 4 - the size of the compressed data
+    // 4 - the size of the uncompressed data
+    // 4 - ???
+    // 4 - the offset from the start of the file where the data resides
+    // 4 - length of the name
+    // x - the name
+    //
+    fn read_file(&mut self, parent: &str, prefix: u32, kind: u8, record: u64) -> Result<FileEntry> {
+        let compression = self.source.read_integer()?;
+        let comp_size = self.source.read_integer()?;
+        let size = self.source.read_integer()?;
+        let unknown = self.source.read_integer()?;
+        let offset = self.source.read_integer()?;
+        let (name, raw_name) = self.read_name(record)?;
+        let path = join_path(parent, &name);
+        Ok(FileEntry {
+            name,
+            raw_name,
+            path,
+            prefix,
+            kind,
+            compressed: compression != 0,
+            compression,
+            comp_size,
+            size,
+            unknown,
+            offset,
+        })
+    }
+
+    fn read_name(&mut self, record: u64) -> Result<(String, Vec<u8>)> {
+        let offset = self.source.position()?;
+        let name_length = self.source.read_integer()?;
+        if name_length > MAX_NAME_LEN {
+            return Err(bad_record(record, format!("name length {} exceeds the limit of {}", name_length, MAX_NAME_LEN)));
+        }
+        if u64::from(name_length) > self.remaining(offset + 4) {
+            return Err(bad_record(record, format!("name length {} runs past the end of the archive", name_length)));
+        }
+        let raw_name = self.source.read_bytes(name_length as usize)?;
+        let name = self.options.encoding.decode(&raw_name).ok_or(HvpError::BadName { offset })?;
+        Ok((name, raw_name.into_owned()))
+    }
+
+    /// Checks that `count` more entry records, on top of those still pending,
+    /// can fit into the archive, and adds them to the pending ones.
+    fn check_count(&mut self, count: u32, record: u64) -> Result<()> {
+        let offset = self.source.position()?;
+        let remaining = self.remaining(offset);
+        if (self.pending + u64::from(count)) * MIN_RECORD_LEN > remaining {
+            return Err(bad_record(
+                record,
+                format!("{} entries cannot fit into the {} bytes left in the archive", self.pending + u64::from(count), remaining),
+            ));
+        }
+        self.pending += u64::from(count);
+        Ok(())
+    }
+
+    fn remaining(&self, offset: u64) -> u64 {
+        self.len.saturating_sub(offset)
+    }
+}
+
+fn bad_record(offset: u64, reason: String) -> HvpError {
+    HvpError::BadRecord { offset, reason }
+}
+
+/// Inflates `compressed` as the data of `entry`.
+pub(crate) fn decompress<D: Read>(compressed: D, entry: &FileEntry) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    inflate_to(compressed, entry, &mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Inflates `compressed` as the data of `entry` into `out`. Inflating stops as
+/// soon as the data grows past the recorded size, so a stream that inflates to
+/// more than that is never read to its end.
+fn inflate_to<D: Read, W: Write>(compressed: D, entry: &FileEntry, out: &mut W) -> Result<u64> {
+    let failed = |_| HvpError::DecompressFailed { entry: entry.path.clone() };
+    let size = u64::from(entry.size);
+    let mut decoder = zlib::Decoder::new(compressed);
+    let mut buf = [0; 8192];
+    let mut written = 0;
+    loop {
+        let n = decoder.read(&mut buf).map_err(failed)?;
+        if n == 0 {
+            break;
+        }
+        if written + n as u64 > size {
+            // Stop right away, the rest of the stream may be arbitrarily long.
+            return Err(HvpError::SizeMismatch { entry: entry.path.clone(), expected: size, actual: written + n as u64 });
+        }
+        out.write_all(&buf[..n])?;
+        written += n as u64;
+    }
+    if written != size {
+        return Err(HvpError::SizeMismatch { entry: entry.path.clone(), expected: size, actual: written });
+    }
+    Ok(written)
+}
+
+fn read_into<R: Read + Seek>(file: &mut R, buf: &mut [u8]) -> Result<()> {
+    let offset = file.stream_position()?;
+    file.read_exact(buf).map_err(|e| truncated(e, offset))
+}
+
+fn read_four<R: Read + Seek>(file: &mut R) -> Result<[u8; 4]> {
+    let mut buf = [0; 4];
+    read_into(file, &mut buf)?;
+    Ok(buf)
+}
+
+fn read_bytes<R: Read + Seek>(file: &mut R, bytes: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0; bytes];
+    read_into(file, &mut buf)?;
+    Ok(buf)
+}
+
+fn truncated(e: io::Error, offset: u64) -> HvpError {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        HvpError::Truncated { offset }
+    } else {
+        HvpError::Io(e)
+    }
+}
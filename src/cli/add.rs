@@ -0,0 +1,11 @@
+use std::fs;
+
+use super::{open_rw, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let (in_file, path, new_file) = (&args.positional[0], &args.positional[1], &args.positional[2]);
+    let mut hvp = open_rw(in_file, args)?;
+    hvp.add(path, &fs::read(new_file)?, !args.flag("--store"))?;
+    println!("Added {}", path);
+    Ok(())
+}
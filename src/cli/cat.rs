@@ -0,0 +1,16 @@
+use std::io::{self, BufWriter, Write};
+use hvp::HvpError;
+
+use super::{open, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let (in_file, path) = (&args.positional[0], &args.positional[1]);
+    let hvp = open(in_file, args)?;
+    let file = hvp.find(path).ok_or_else(|| HvpError::NotFound { path: path.clone() })?;
+    let mut out = BufWriter::new(io::stdout().lock());
+    match hvp.read_to(file, &mut out).and_then(|_| Ok(out.flush()?)) {
+        // The reader went away, e.g. `head`, which is not an error.
+        Err(HvpError::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
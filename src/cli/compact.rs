@@ -0,0 +1,26 @@
+use std::{
+    error::Error, fs::{self, File}, io::BufWriter
+};
+use hvp::HvpArchive;
+
+use super::{open, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let in_file = &args.positional[0];
+    let tmp_file = format!("{}.tmp", in_file);
+    let reclaimed = {
+        let hvp = open(in_file, args)?;
+        compact(&hvp, &tmp_file).inspect_err(|_| {
+            // Do not leave a half-written copy behind.
+            let _ = fs::remove_file(&tmp_file);
+        })?
+    };
+    fs::rename(&tmp_file, in_file)?;
+    println!("Compacted {}, reclaimed {} bytes", in_file, reclaimed);
+    Ok(())
+}
+
+fn compact(hvp: &HvpArchive, tmp_file: &str) -> Result<u64, Box<dyn Error>> {
+    let mut out = BufWriter::new(File::create(tmp_file)?);
+    Ok(hvp.compact_to(&mut out)?)
+}
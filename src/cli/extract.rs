@@ -0,0 +1,137 @@
+use std::{
+    env::current_dir, fs::{create_dir_all, remove_file, File}, io::{self, BufWriter, Write}, path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering}, thread
+};
+use hvp::{is_safe_name, FileEntry, HvpArchive, HvpEntry};
+
+use super::{filter::Filter, open, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let in_file: &str = &args.positional[0];
+    let out_dir = match args.positional.get(1) {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => current_dir()?,
+    };
+    if !out_dir.exists() {
+        return Err(format!("Output directory {} does not exist!", out_dir.display()).into());
+    }
+
+    let jobs = match args.value("-j") {
+        Some(jobs) => jobs.parse().ok().filter(|&jobs| jobs > 0).ok_or_else(|| format!("invalid number of jobs {}", jobs))?,
+        None => 1,
+    };
+
+    let hvp = open(in_file, args)?;
+    let mut extractor = Extractor {
+        hvp: &hvp,
+        filter: Filter::from_args(args)?,
+        allow_unsafe_paths: args.flag("--allow-unsafe-paths"),
+        refused: 0,
+        files: Vec::new(),
+    };
+    for entry in hvp.entries() {
+        extractor.extract(entry, &out_dir)?;
+    }
+    if jobs == 1 {
+        for (file, path) in &extractor.files {
+            write_file(path, |out| hvp.read_to(file, out))?;
+        }
+    } else {
+        extract_parallel(&hvp, &extractor.files, jobs)?;
+    }
+    if let Some(manifest) = args.value("--raw-manifest") {
+        hvp::write_manifest(&hvp, Path::new(manifest))?;
+        println!("Wrote manifest {}", manifest);
+    }
+    if extractor.refused > 0 {
+        return Err(format!("Skipped {} entries with unsafe names, use --allow-unsafe-paths to extract them", extractor.refused).into());
+    }
+    Ok(())
+}
+
+struct Extractor<'a> {
+    hvp: &'a HvpArchive,
+    filter: Filter,
+    allow_unsafe_paths: bool,
+    refused: usize,
+    /// The selected files and where to write them.
+    files: Vec<(&'a FileEntry, PathBuf)>,
+}
+
+impl<'a> Extractor<'a> {
+    // Creates the directories below `path` and queues up the files to write.
+    fn extract(&mut self, entry: &'a HvpEntry, path: &Path) -> hvp::Result<()> {
+        if !self.filter.selects(entry) {
+            return Ok(());
+        }
+        if !self.allow_unsafe_paths && !is_safe_name(entry.name()) {
+            println!(
+                "Refusing unsafe path {} (record at offset {:#x})",
+                entry.path(),
+                self.hvp.record_offset(entry.path()).unwrap_or_default()
+            );
+            self.refused += 1;
+            return Ok(());
+        }
+        let path = path.join(entry.name());
+        match entry {
+            HvpEntry::Directory(dir) => {
+                create_dir(&path)?;
+                for child in &dir.children {
+                    self.extract(child, &path)?;
+                }
+            }
+            HvpEntry::File(file) => self.files.push((file, path)),
+        }
+        Ok(())
+    }
+}
+
+// Hands the files out to `jobs` threads, stopping them all at the first error.
+fn extract_parallel(hvp: &HvpArchive, files: &[(&FileEntry, PathBuf)], jobs: usize) -> hvp::Result<()> {
+    let reader = hvp.shared_reader()?;
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let worker = || -> hvp::Result<()> {
+        while !failed.load(Ordering::Relaxed) {
+            let Some((file, path)) = files.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                break;
+            };
+            let result = write_file(path, |out| reader.read_to(file, out));
+            if result.is_err() {
+                failed.store(true, Ordering::Relaxed);
+                return result;
+            }
+        }
+        Ok(())
+    };
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs).map(|_| scope.spawn(worker)).collect();
+        workers.into_iter().try_for_each(|worker| worker.join().expect("extraction thread panicked"))
+    })
+}
+
+// Streams the contents of a file into `path` with `read_to`, removing what
+// was written if that fails.
+fn write_file<F>(path: &Path, read_to: F) -> hvp::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> hvp::Result<u64>,
+{
+    let mut out = BufWriter::new(create_file(path)?);
+    let result = read_to(&mut out).and_then(|_| Ok(out.flush()?));
+    if result.is_err() {
+        drop(out);
+        let _ = remove_file(path);
+    }
+    result
+}
+
+fn create_dir(path: &Path) -> io::Result<()> {
+    println!("Creating dir {}", path.display());
+    create_dir_all(path)
+}
+
+fn create_file(path: &Path) -> io::Result<File> {
+    println!("Creating file {}", path.display());
+    File::create(path)
+}
@@ -0,0 +1,125 @@
+use std::error::Error;
+use hvp::HvpEntry;
+use regex::Regex;
+
+use super::Args;
+
+/// Selects entries by their full path with `--include`, `--exclude` and
+/// `--regex`.
+pub struct Filter {
+    include: Vec<Vec<char>>,
+    exclude: Vec<Vec<char>>,
+    regex: Option<Regex>,
+}
+
+impl Filter {
+    pub fn from_args(args: &Args) -> Result<Filter, Box<dyn Error>> {
+        let patterns = |name| args.values(name).iter().map(|pattern| pattern.chars().collect()).collect();
+        Ok(Filter {
+            include: patterns("--include"),
+            exclude: patterns("--exclude"),
+            regex: args.value("--regex").map(Regex::new).transpose()?,
+        })
+    }
+
+    /// Whether every entry is selected.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.regex.is_none()
+    }
+
+    /// Whether the file at `path` is selected. Without `--include` patterns
+    /// every path not excluded is.
+    pub fn matches(&self, path: &str) -> bool {
+        let chars: Vec<char> = path.chars().collect();
+        (self.include.is_empty() || self.include.iter().any(|pattern| glob(pattern, &chars)))
+            && !self.exclude.iter().any(|pattern| glob(pattern, &chars))
+            && self.regex.as_ref().is_none_or(|regex| regex.is_match(path))
+    }
+
+    /// Whether `entry` is or holds a selected file.
+    pub fn selects(&self, entry: &HvpEntry) -> bool {
+        match entry {
+            HvpEntry::Directory(dir) => self.is_empty() || dir.children.iter().any(|child| self.selects(child)),
+            HvpEntry::File(file) => self.matches(&file.path),
+        }
+    }
+}
+
+// `*` matches within one path component, `**` across components, `?` any one
+// character but `/` and `[...]` one character of a set such as `[a-z]` or `[!0-9]`.
+fn glob(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // Let `a/**/b` match `a/b` as well.
+            let skip_slash = rest.first() == Some(&'/') && glob(&rest[1..], path);
+            skip_slash || (0..=path.len()).any(|i| glob(rest, &path[i..]))
+        }
+        ['*', rest @ ..] => {
+            let component = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=component).any(|i| glob(rest, &path[i..]))
+        }
+        ['?', rest @ ..] => matches!(path, [c, ..] if *c != '/') && glob(rest, &path[1..]),
+        ['[', class @ ..] => match class.iter().skip(1).position(|&c| c == ']') {
+            Some(end) => match path {
+                [c, ..] => in_class(&class[..end + 1], *c) && glob(&class[end + 2..], &path[1..]),
+                [] => false,
+            },
+            None => path.first() == Some(&'[') && glob(class, &path[1..]),
+        },
+        [c, rest @ ..] => path.first() == Some(c) && glob(rest, &path[1..]),
+    }
+}
+
+fn in_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated && c != '/'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        glob(&pattern.chars().collect::<Vec<_>>(), &path.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn double_star_spans_any_number_of_components() {
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        assert!(matches("**/*.wav", "x.wav"));
+        assert!(matches("**/*.wav", "sound/x.wav"));
+        assert!(!matches("a/**/b", "ab"));
+        assert!(!matches("*.wav", "sound/x.wav"));
+    }
+
+    #[test]
+    fn negated_class_matches_characters_outside_it() {
+        assert!(matches("[!0-9].txt", "a.txt"));
+        assert!(!matches("[!0-9].txt", "5.txt"));
+        assert!(!matches("a[!x]b", "a/b"));
+        assert!(in_class(&['a', '-', 'c'], 'b'));
+        assert!(!in_class(&['!', 'a', '-', 'c'], 'b'));
+    }
+
+    #[test]
+    fn unterminated_class_is_a_literal_bracket() {
+        assert!(matches("a[bc", "a[bc"));
+        assert!(!matches("a[bc", "ab"));
+    }
+}
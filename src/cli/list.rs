@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+use hvp::{FileEntry, HvpEntry};
+
+use super::{open, Args, CliResult};
+
+#[derive(Clone, Copy)]
+enum SortBy {
+    Name,
+    Size,
+    Offset,
+}
+
+pub fn run(args: &Args) -> CliResult {
+    let sort = match args.value("--sort") {
+        None => None,
+        Some("name") => Some(SortBy::Name),
+        Some("size") => Some(SortBy::Size),
+        Some("offset") => Some(SortBy::Offset),
+        Some(other) => return Err(format!("cannot sort by {}, expected name, size or offset", other).into()),
+    };
+    let hvp = open(&args.positional[0], args)?;
+
+    println!("{:>10} {:>10} {:>6} {:>10} {:>4}  Name", "Size", "Packed", "Ratio", "Offset", "Zlib");
+    if args.flag("--tree") {
+        print_tree(hvp.entries(), sort, 0);
+    } else {
+        let mut files: Vec<&FileEntry> = hvp.files().collect();
+        if let Some(sort) = sort {
+            files.sort_by(|a, b| compare_files(a, b, sort));
+        }
+        for file in files {
+            print_file(file, &file.path);
+        }
+    }
+
+    let (count, size, packed) = hvp.files().fold((0, 0u64, 0u64), |(count, size, packed), file| {
+        (count + 1, size + u64::from(file.size), packed + u64::from(file.stored_len()))
+    });
+    println!("{:>10} {:>10} {:>6} {:>10} {:>4}  {} files", size, packed, ratio(packed, size), "", "", count);
+    Ok(())
+}
+
+fn print_tree(entries: &[HvpEntry], sort: Option<SortBy>, depth: usize) {
+    let mut entries: Vec<&HvpEntry> = entries.iter().collect();
+    if let Some(sort) = sort {
+        entries.sort_by(|a, b| compare_entries(a, b, sort));
+    }
+    let indent = "  ".repeat(depth);
+    for entry in entries {
+        match entry {
+            HvpEntry::Directory(dir) => {
+                println!("{:>10} {:>10} {:>6} {:>10} {:>4}  {}{}/", "", "", "", "", "", indent, dir.name);
+                print_tree(&dir.children, sort, depth + 1);
+            }
+            HvpEntry::File(file) => print_file(file, &format!("{}{}", indent, file.name)),
+        }
+    }
+}
+
+fn print_file(file: &FileEntry, name: &str) {
+    println!(
+        "{:>10} {:>10} {:>6} {:>#10x} {:>4}  {}",
+        file.size,
+        file.stored_len(),
+        ratio(file.stored_len().into(), file.size.into()),
+        file.offset,
+        if file.compressed { "yes" } else { "no" },
+        name
+    );
+}
+
+fn ratio(packed: u64, size: u64) -> String {
+    if size == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", packed as f64 * 100.0 / size as f64)
+    }
+}
+
+fn compare_files(a: &FileEntry, b: &FileEntry, sort: SortBy) -> Ordering {
+    match sort {
+        SortBy::Name => a.path.cmp(&b.path),
+        SortBy::Size => a.size.cmp(&b.size),
+        SortBy::Offset => a.offset.cmp(&b.offset),
+    }
+}
+
+// Directories sort by the total size and the lowest offset of their contents.
+fn compare_entries(a: &HvpEntry, b: &HvpEntry, sort: SortBy) -> Ordering {
+    match sort {
+        SortBy::Name => a.name().cmp(b.name()),
+        SortBy::Size => total_size(a).cmp(&total_size(b)),
+        SortBy::Offset => first_offset(a).cmp(&first_offset(b)),
+    }
+}
+
+fn total_size(entry: &HvpEntry) -> u64 {
+    match entry {
+        HvpEntry::Directory(dir) => dir.children.iter().map(total_size).sum(),
+        HvpEntry::File(file) => file.size.into(),
+    }
+}
+
+fn first_offset(entry: &HvpEntry) -> u32 {
+    match entry {
+        HvpEntry::Directory(dir) => dir.children.iter().map(first_offset).min().unwrap_or(u32::MAX),
+        HvpEntry::File(file) => file.offset,
+    }
+}
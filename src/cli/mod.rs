@@ -0,0 +1,123 @@
+use std::error::Error;
+use hvp::{HvpArchive, HvpError, NameEncoding, ParseOptions};
+
+pub mod add;
+pub mod cat;
+pub mod compact;
+pub mod extract;
+pub mod filter;
+pub mod list;
+pub mod pack;
+pub mod replace;
+pub mod rm;
+pub mod salvage;
+pub mod test;
+
+pub type CliResult = Result<(), Box<dyn Error>>;
+
+/// Command line arguments split into `--options` and positional arguments.
+pub struct Args {
+    options: Vec<(String, Option<String>)>,
+    pub positional: Vec<String>,
+}
+
+impl Args {
+    /// Splits `args`. Options listed in `with_value` consume the following
+    /// argument, every other `--option` is a plain flag.
+    pub fn parse(args: &[String], flags: &[&str], with_value: &[&str]) -> Result<Args, String> {
+        let mut options = Vec::new();
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if with_value.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| format!("{} requires a value", arg))?;
+                options.push((arg.clone(), Some(value.clone())));
+            } else if flags.contains(&arg.as_str()) {
+                options.push((arg.clone(), None));
+            } else if arg.starts_with('-') && arg.len() > 1 {
+                return Err(format!("unknown option {}", arg));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Args { options, positional })
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).pop()
+    }
+
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.options.iter().filter(|(option, _)| option == name).filter_map(|(_, value)| value.as_deref()).collect()
+    }
+}
+
+/// The name encoding chosen with `--encoding`.
+pub fn encoding(args: &Args) -> Result<NameEncoding, Box<dyn Error>> {
+    Ok(args.value("--encoding").map(str::parse).transpose()?.unwrap_or_default())
+}
+
+pub fn parse_options(args: &Args) -> Result<ParseOptions, Box<dyn Error>> {
+    let mut options = ParseOptions { encoding: encoding(args)?, ..ParseOptions::default() };
+    if let Some(size) = args.value("--max-entry-size") {
+        options.max_entry_size = parse_size(size)?;
+    }
+    if let Some(size) = args.value("--max-total-size") {
+        options.max_total_size = parse_size(size)?;
+    }
+    Ok(options)
+}
+
+/// Parses a number of bytes with an optional K, M or G suffix.
+fn parse_size(size: &str) -> Result<u64, String> {
+    let (digits, unit) = match size.to_ascii_uppercase().chars().last() {
+        Some('K') => (&size[..size.len() - 1], 1 << 10),
+        Some('M') => (&size[..size.len() - 1], 1 << 20),
+        Some('G') => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| format!("invalid size {}, expected a number of bytes with an optional K, M or G suffix", size))
+}
+
+pub fn open(in_file: &str, args: &Args) -> Result<HvpArchive, Box<dyn Error>> {
+    HvpArchive::open_with(in_file, &parse_options(args)?).map_err(|e| open_error(in_file, e))
+}
+
+pub fn open_rw(in_file: &str, args: &Args) -> Result<HvpArchive, Box<dyn Error>> {
+    HvpArchive::open_rw(in_file, &parse_options(args)?).map_err(|e| open_error(in_file, e))
+}
+
+fn open_error(in_file: &str, e: HvpError) -> Box<dyn Error> {
+    match e {
+        HvpError::BadMagic => format!("{} is not a valid HV PackFile", in_file).into(),
+        e => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_take_binary_suffixes() {
+        assert_eq!(parse_size("100"), Ok(100));
+        assert_eq!(parse_size("4k"), Ok(4 << 10));
+        assert_eq!(parse_size("2M"), Ok(2 << 20));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+    }
+
+    #[test]
+    fn malformed_sizes_are_rejected() {
+        for size in ["", "K", "1.5M", "-1", "1T", "99999999999G"] {
+            assert!(parse_size(size).is_err(), "{:?}", size);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use super::{encoding, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let in_dir = Path::new(&args.positional[0]);
+    if !in_dir.is_dir() {
+        return Err(format!("Input directory {} does not exist!", in_dir.display()).into());
+    }
+    let mut out = BufWriter::new(File::create(&args.positional[1])?);
+    match args.value("--from-manifest") {
+        Some(manifest) => hvp::pack_manifest(Path::new(manifest), in_dir, &mut out)?,
+        None => hvp::pack_dir(in_dir, &mut out, !args.flag("--store"), encoding(args)?)?,
+    }
+    println!("Packed {} into {}", in_dir.display(), args.positional[1]);
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use std::fs;
+
+use super::{open_rw, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let (in_file, path, new_file) = (&args.positional[0], &args.positional[1], &args.positional[2]);
+    let mut hvp = open_rw(in_file, args)?;
+    let old_offset = hvp.find(path).map(|file| file.offset);
+    hvp.replace(path, &fs::read(new_file)?)?;
+
+    let file = hvp.find(path).expect("replaced file is in the archive");
+    if Some(file.offset) == old_offset {
+        println!("Replaced {} in place", path);
+    } else {
+        println!("Replaced {}, moved its data to offset {:#x}", path, file.offset);
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use super::{open_rw, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let mut hvp = open_rw(&args.positional[0], args)?;
+    for path in &args.positional[1..] {
+        hvp.remove(path)?;
+        println!("Removed {}", path);
+    }
+    Ok(())
+}
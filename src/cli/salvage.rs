@@ -0,0 +1,43 @@
+use std::{
+    env::current_dir, fs::{self, create_dir_all, File}, io::Write, path::PathBuf
+};
+
+use super::{parse_options, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let in_file = &args.positional[0];
+    let out_dir = match args.positional.get(1) {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => current_dir()?,
+    };
+    if !out_dir.exists() {
+        return Err(format!("Output directory {} does not exist!", out_dir.display()).into());
+    }
+
+    let archive = fs::read(in_file)?;
+    let mut report = File::create(out_dir.join("salvage_report.txt"))?;
+    writeln!(report, "Salvaged from {}", in_file)?;
+    let (mut named, mut unnamed) = (0, 0);
+    hvp::salvage(&archive, &parse_options(args)?, |item| {
+        let (path, source) = match &item.path {
+            Some(path) => {
+                named += 1;
+                (out_dir.join(path), "record")
+            }
+            None => {
+                unnamed += 1;
+                (out_dir.join(format!("recovered_{}.bin", item.offset)), "zlib scan")
+            }
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        println!("Recovered {} bytes at offset {:#x} to {}", item.data.len(), item.offset, path.display());
+        fs::write(&path, &item.data)?;
+        writeln!(report, "{:#010x} {:>10} {:<9} {}", item.offset, item.data.len(), source, path.display())?;
+        Ok(())
+    })?;
+    writeln!(report, "{} files recovered by their records, {} by scanning", named, unnamed)?;
+    println!("{} files recovered by their records, {} by scanning, see salvage_report.txt", named, unnamed);
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use super::{open, Args, CliResult};
+
+pub fn run(args: &Args) -> CliResult {
+    let in_file = &args.positional[0];
+    let hvp = open(in_file, args)?;
+    let failures = hvp.verify();
+    for (file, e) in &failures {
+        println!("FAILED {} at offset {:#x}: {}", file.path, file.offset, e);
+    }
+    for entries in hvp.case_collisions() {
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path()).collect();
+        println!("WARNING names only differ in case: {}", paths.join(", "));
+    }
+    let count = hvp.files().count();
+    if !failures.is_empty() {
+        return Err(format!("{} of {} files in {} failed", failures.len(), count, in_file).into());
+    }
+    println!("No errors detected in {} files of {}", count, in_file);
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap, io::{Read, Seek, SeekFrom, Write}
+};
+
+use crate::archive::{HvpArchive, MAX_DEPTH};
+use crate::entry::{for_each_file_mut, is_safe_name, join_path, DirEntry, FileEntry, HvpEntry};
+use crate::error::{HvpError, Result};
+use crate::index::{normalize, resolve, resolve_mut, Index};
+use crate::name::NameEncoding;
+use crate::writer::{encode, encode_name, record_offset, table_len, to_u32, write_file_fields, write_table};
+
+impl<F: Read + Write + Seek> HvpArchive<F> {
+    /// Replaces the contents of the file at `path` with `data`, compressing
+    /// them if the file was compressed before. The new data overwrites the
+    /// old one if it fits and no other file shares it, otherwise it is
+    /// appended to the end of the archive. Only the record of the replaced
+    /// file is rewritten. `path` is looked up like [`HvpArchive::get`] does.
+    pub fn replace(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let at = self.index.position(path).ok_or_else(|| not_found(path))?.to_vec();
+        let HvpEntry::File(file) = resolve(&self.entries, &at) else {
+            return Err(not_found(path));
+        };
+        let record = record_offset(&self.entries, &file.path).ok_or_else(|| not_found(path))?;
+        // Data other files share has to stay as it is.
+        let shared = self.files().filter(|other| overlaps(file, other)).count() > 1;
+        let HvpEntry::File(file) = resolve_mut(&mut self.entries, &at) else {
+            unreachable!("the entry is a file")
+        };
+        let room = if shared { 0 } else { file.stored_len() as usize };
+        let blob = if file.compressed { encode(data)? } else { None };
+        file.compressed = blob.is_some();
+        let blob = blob.as_deref().unwrap_or(data);
+        if blob.len() > room {
+            file.offset = to_u32(self.len, path)?;
+            self.len += blob.len() as u64;
+        }
+        file.comp_size = to_u32(blob.len() as u64, path)?;
+        file.size = to_u32(data.len() as u64, path)?;
+
+        let out = self.file.get_mut();
+        out.seek(SeekFrom::Start(file.offset.into()))?;
+        out.write_all(blob)?;
+        // Skip the prefix and the entry type.
+        out.seek(SeekFrom::Start(record + 5))?;
+        write_file_fields(out, file)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Adds a file at `path` holding `data`, creating missing parent
+    /// directories. The data is appended to the end of the archive, zlib
+    /// compressed if `compress` is set and that makes it smaller. New names
+    /// are written in the encoding the archive was opened with. Names that
+    /// are not safe to extract to and paths nested too deep to be parsed
+    /// again are rejected.
+    pub fn add(&mut self, path: &str, data: &[u8], compress: bool) -> Result<()> {
+        let path = &normalize(path);
+        if !path.split('/').all(is_safe_name) {
+            return Err(HvpError::UnsafeName { path: path.to_string() });
+        }
+        // Deeper archives could not be opened again.
+        if path.split('/').count() - 1 > MAX_DEPTH {
+            return Err(HvpError::TooDeep { path: path.to_string(), limit: MAX_DEPTH });
+        }
+        if self.index.position(path).is_some() {
+            return Err(HvpError::Exists { path: path.to_string() });
+        }
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let raw_name = encode_name(name, self.options.encoding)?;
+        let siblings = children_mut(&mut self.entries, parent, self.options.encoding)?;
+
+        let blob = if compress { encode(data)? } else { None };
+        let compressed = blob.is_some();
+        let blob = blob.as_deref().unwrap_or(data);
+        siblings.push(HvpEntry::File(FileEntry {
+            name: name.to_string(),
+            raw_name,
+            path: path.to_string(),
+            prefix: 0,
+            kind: 1,
+            compressed,
+            compression: compressed.into(),
+            comp_size: to_u32(blob.len() as u64, path)?,
+            size: to_u32(data.len() as u64, path)?,
+            unknown: 0,
+            offset: to_u32(self.len, path)?,
+        }));
+        let out = self.file.get_mut();
+        out.seek(SeekFrom::Start(self.len))?;
+        out.write_all(blob)?;
+        self.len += blob.len() as u64;
+
+        self.rewrite_table()
+    }
+
+    /// Removes the file or directory at `path`. The data of removed files is
+    /// left in place as unreferenced space. `path` is looked up like
+    /// [`HvpArchive::get`] does.
+    pub fn remove(&mut self, path: &str) -> Result<()> {
+        let at = self.index.position(path).ok_or_else(|| not_found(path))?;
+        let (last, parent) = at.split_last().expect("index paths are not empty");
+        let siblings = match parent {
+            [] => &mut self.entries,
+            _ => match resolve_mut(&mut self.entries, parent) {
+                HvpEntry::Directory(dir) => &mut dir.children,
+                HvpEntry::File(_) => unreachable!("files have no children"),
+            },
+        };
+        siblings.remove(*last);
+        self.rewrite_table()
+    }
+
+    // Writes all entry records, moving the data of files the records would
+    // grow into to the end of the archive first.
+    fn rewrite_table(&mut self) -> Result<()> {
+        self.index = Index::new(&self.entries);
+        let table_end = table_len(&self.entries);
+        // Data moved out of the way has to land behind the table, even if
+        // the table grows past the current end of the archive.
+        self.len = self.len.max(table_end);
+        let out = self.file.get_mut();
+        let len = &mut self.len;
+        for_each_file_mut(&mut self.entries, &mut |file| {
+            if u64::from(file.offset) >= table_end || file.stored_len() == 0 {
+                return Ok(());
+            }
+            let mut blob = vec![0; file.stored_len() as usize];
+            out.seek(SeekFrom::Start(file.offset.into()))?;
+            out.read_exact(&mut blob)?;
+            out.seek(SeekFrom::Start(*len))?;
+            out.write_all(&blob)?;
+            file.offset = to_u32(*len, &file.path)?;
+            *len += blob.len() as u64;
+            Ok::<_, HvpError>(())
+        })?;
+
+        out.rewind()?;
+        write_table(out, &self.header, &self.entries)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> HvpArchive<R> {
+    /// Writes a copy of the archive to `out` with the data of all files laid
+    /// out contiguously in directory order, dropping any bytes no entry
+    /// refers to. Compressed data is copied as-is. Returns the number of bytes
+    /// reclaimed.
+    pub fn compact_to<W: Write + Seek>(&self, out: &mut W) -> Result<u64> {
+        let mut entries = self.entries.clone();
+        let mut end = table_len(&entries);
+        // Entries sharing the same data keep sharing it.
+        let mut moved = HashMap::new();
+        out.seek(SeekFrom::Start(end))?;
+        for_each_file_mut(&mut entries, &mut |file| {
+            let key = (file.offset, file.stored_len());
+            file.offset = match moved.get(&key) {
+                Some(&offset) => offset,
+                None => {
+                    out.write_all(&self.read_raw(file)?)?;
+                    let offset = to_u32(end, &file.path)?;
+                    end += u64::from(file.stored_len());
+                    moved.insert(key, offset);
+                    offset
+                }
+            };
+            Ok::<_, HvpError>(())
+        })?;
+
+        out.rewind()?;
+        write_table(out, &self.header, &entries)?;
+        out.flush()?;
+        Ok(self.len.saturating_sub(end))
+    }
+}
+
+// Whether the stored data of `a` and `b` have any byte in common.
+fn overlaps(a: &FileEntry, b: &FileEntry) -> bool {
+    let end = |file: &FileEntry| u64::from(file.offset) + u64::from(file.stored_len());
+    u64::from(a.offset) < end(b) && u64::from(b.offset) < end(a)
+}
+
+fn not_found(path: &str) -> HvpError {
+    HvpError::NotFound { path: path.to_string() }
+}
+
+// The children of the directory at `path`, creating it and its parents if
+// they do not exist yet.
+fn children_mut<'a>(entries: &'a mut Vec<HvpEntry>, path: &str, encoding: NameEncoding) -> Result<&'a mut Vec<HvpEntry>> {
+    let mut children = entries;
+    let mut parent = String::new();
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        let dir_path = join_path(&parent, name);
+        let index = match children.iter().position(|entry| entry.name() == name) {
+            Some(index) => index,
+            None => {
+                children.push(HvpEntry::Directory(DirEntry {
+                    name: name.to_string(),
+                    raw_name: encode_name(name, encoding)?,
+                    path: dir_path.clone(),
+                    prefix: 0,
+                    unknown: 0,
+                    children: Vec::new(),
+                }));
+                children.len() - 1
+            }
+        };
+        children = match &mut children[index] {
+            HvpEntry::Directory(dir) => &mut dir.children,
+            HvpEntry::File(_) => return Err(HvpError::Exists { path: dir_path }),
+        };
+        parent = dir_path;
+    }
+    Ok(children)
+}
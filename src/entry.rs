@@ -0,0 +1,133 @@
+use std::slice;
+
+#[derive(Debug, Clone)]
+pub enum HvpEntry {
+    Directory(DirEntry),
+    File(FileEntry),
+}
+
+/// The header fields surrounding the entry count, meaning unknown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    pub unknown1: [u8; 5],
+    pub unknown2: [u8; 20],
+}
+
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub name: String,
+    /// The name exactly as stored in the archive.
+    pub raw_name: Vec<u8>,
+    /// Full path inside the archive, components separated by `/`.
+    pub path: String,
+    /// The 4 bytes in front of the entry type.
+    pub prefix: u32,
+    pub unknown: u32,
+    pub children: Vec<HvpEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    pub name: String,
+    /// The name exactly as stored in the archive.
+    pub raw_name: Vec<u8>,
+    /// Full path inside the archive, components separated by `/`.
+    pub path: String,
+    /// The 4 bytes in front of the entry type.
+    pub prefix: u32,
+    /// The entry type as stored, anything but 0.
+    pub kind: u8,
+    pub compressed: bool,
+    /// The compression flag as stored, `compressed` tells whether it is set.
+    pub compression: u32,
+    pub comp_size: u32,
+    pub size: u32,
+    pub unknown: u32,
+    pub offset: u32,
+}
+
+impl FileEntry {
+    /// The length of the data as stored in the archive, `comp_size` for
+    /// compressed files and `size` otherwise.
+    pub fn stored_len(&self) -> u32 {
+        if self.compressed {
+            self.comp_size
+        } else {
+            self.size
+        }
+    }
+}
+
+impl HvpEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            HvpEntry::Directory(dir) => &dir.name,
+            HvpEntry::File(file) => &file.name,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            HvpEntry::Directory(dir) => &dir.path,
+            HvpEntry::File(file) => &file.path,
+        }
+    }
+}
+
+/// Whether `name` is a single path component that cannot point outside the
+/// directory it is extracted to, i.e. it is not empty, `.` or `..` and holds
+/// no path separators or drive letters.
+pub fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':', '\0'])
+}
+
+/// Depth-first iterator over an entry tree, yielding each directory before
+/// its children.
+pub struct Walk<'a> {
+    stack: Vec<slice::Iter<'a, HvpEntry>>,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(entries: &'a [HvpEntry]) -> Walk<'a> {
+        Walk { stack: vec![entries.iter()] }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = &'a HvpEntry;
+
+    fn next(&mut self) -> Option<&'a HvpEntry> {
+        loop {
+            let entry = match self.stack.last_mut()?.next() {
+                Some(entry) => entry,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            if let HvpEntry::Directory(dir) = entry {
+                self.stack.push(dir.children.iter());
+            }
+            return Some(entry);
+        }
+    }
+}
+
+/// Calls `f` on every file entry of the tree, depth-first.
+pub(crate) fn for_each_file_mut<E>(entries: &mut [HvpEntry], f: &mut impl FnMut(&mut FileEntry) -> Result<(), E>) -> Result<(), E> {
+    for entry in entries {
+        match entry {
+            HvpEntry::Directory(dir) => for_each_file_mut(&mut dir.children, f)?,
+            HvpEntry::File(file) => f(file)?,
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn join_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
@@ -0,0 +1,87 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum HvpError {
+    /// The file does not start with the "HV PackFile" tag.
+    BadMagic,
+    /// The archive ended while reading the field at `offset`.
+    Truncated { offset: u64 },
+    /// The entry record at `offset` holds a value that cannot be right.
+    BadRecord { offset: u64, reason: String },
+    /// The name of the record at `offset` could not be decoded.
+    BadName { offset: u64 },
+    /// The zlib stream of `entry` could not be inflated.
+    DecompressFailed { entry: String },
+    /// The data of `entry` does not have the length recorded in its header.
+    /// Data longer than recorded is not inflated to its end, `actual` is then
+    /// only how far it got.
+    SizeMismatch { entry: String, expected: u64, actual: u64 },
+    /// The archive has no entry at `path`.
+    NotFound { path: String },
+    /// The archive already has an entry at `path`.
+    Exists { path: String },
+    /// `path` has a component that is not safe to extract to, see
+    /// [`is_safe_name`](crate::is_safe_name).
+    UnsafeName { path: String },
+    /// `path` lies in more nested directories than archives may hold.
+    TooDeep { path: String, limit: usize },
+    /// Line `line` of a raw manifest could not be parsed.
+    BadManifest { line: usize },
+    /// The `len` bytes of data of `entry` at `offset` extend past the end of
+    /// the archive.
+    OutOfBounds { entry: String, offset: u64, len: u64 },
+    /// `entry` is larger than the per-entry limit of `limit` bytes.
+    EntryTooLarge { entry: String, limit: u64 },
+    /// The entries read add up to more than the limit of `limit` bytes.
+    TotalTooLarge { limit: u64 },
+    /// `entry` does not fit in the 32-bit fields of the format.
+    TooLarge { entry: String },
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, HvpError>;
+
+impl fmt::Display for HvpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HvpError::BadMagic => write!(f, "not a valid HV PackFile"),
+            HvpError::Truncated { offset } => write!(f, "archive is truncated at offset {}", offset),
+            HvpError::BadRecord { offset, reason } => write!(f, "bad record at offset {}: {}", offset, reason),
+            HvpError::BadName { offset } => write!(f, "invalid entry name at offset {}", offset),
+            HvpError::DecompressFailed { entry } => write!(f, "failed to decompress {}", entry),
+            HvpError::SizeMismatch { entry, expected, actual } if actual > expected => {
+                write!(f, "{} should be {} bytes but is at least {} bytes", entry, expected, actual)
+            }
+            HvpError::SizeMismatch { entry, expected, actual } => {
+                write!(f, "{} should be {} bytes but is {} bytes", entry, expected, actual)
+            }
+            HvpError::NotFound { path } => write!(f, "{} is not in the archive", path),
+            HvpError::Exists { path } => write!(f, "{} already exists in the archive", path),
+            HvpError::UnsafeName { path } => write!(f, "{} is not a safe path to add", path),
+            HvpError::TooDeep { path, limit } => write!(f, "{} is nested deeper than {} directories", path, limit),
+            HvpError::BadManifest { line } => write!(f, "invalid manifest line {}", line),
+            HvpError::OutOfBounds { entry, offset, len } => {
+                write!(f, "{} has {} bytes of data at offset {} past the end of the archive", entry, len, offset)
+            }
+            HvpError::EntryTooLarge { entry, limit } => write!(f, "{} is larger than the limit of {} bytes", entry, limit),
+            HvpError::TotalTooLarge { limit } => write!(f, "the extracted data exceeds the limit of {} bytes", limit),
+            HvpError::TooLarge { entry } => write!(f, "{} is too large for an HV PackFile", entry),
+            HvpError::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for HvpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HvpError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HvpError {
+    fn from(e: io::Error) -> Self {
+        HvpError::Io(e)
+    }
+}
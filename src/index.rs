@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::archive::HvpArchive;
+use crate::entry::HvpEntry;
+
+/// Maps the full paths of all entries to where they are in the entry tree,
+/// once as they are and once case-folded.
+#[derive(Debug, Default)]
+pub(crate) struct Index {
+    exact: HashMap<String, Vec<usize>>,
+    folded: HashMap<String, Vec<Vec<usize>>>,
+}
+
+impl Index {
+    pub(crate) fn new(entries: &[HvpEntry]) -> Index {
+        let mut index = Index::default();
+        index.insert(entries, &mut Vec::new());
+        index
+    }
+
+    fn insert(&mut self, entries: &[HvpEntry], at: &mut Vec<usize>) {
+        for (i, entry) in entries.iter().enumerate() {
+            at.push(i);
+            let path = normalize(entry.path());
+            self.folded.entry(path.to_lowercase()).or_default().push(at.clone());
+            self.exact.entry(path).or_insert_with(|| at.clone());
+            if let HvpEntry::Directory(dir) = entry {
+                self.insert(&dir.children, at);
+            }
+            at.pop();
+        }
+    }
+
+    /// Where the entry at `path` is in the entry tree, components separated
+    /// by `/` or `\`.
+    pub(crate) fn position(&self, path: &str) -> Option<&[usize]> {
+        self.exact.get(&normalize(path)).map(Vec::as_slice)
+    }
+}
+
+impl<R> HvpArchive<R> {
+    /// The entry at `path`, components separated by `/` or `\`.
+    pub fn get(&self, path: &str) -> Option<&HvpEntry> {
+        let at = self.index.position(path)?;
+        Some(resolve(&self.entries, at))
+    }
+
+    /// The entry at `path` ignoring case, as Windows would look it up. An
+    /// exact match wins if there is one, otherwise the first match in the
+    /// archive.
+    pub fn get_ci(&self, path: &str) -> Option<&HvpEntry> {
+        let path = normalize(path);
+        let at = match self.index.exact.get(&path) {
+            Some(at) => at,
+            None => self.index.folded.get(&path.to_lowercase())?.first()?,
+        };
+        Some(resolve(&self.entries, at))
+    }
+
+    /// Groups of entries in the same directory whose names only differ in
+    /// case, which would overwrite each other when extracted on Windows.
+    pub fn case_collisions(&self) -> Vec<Vec<&HvpEntry>> {
+        let mut collisions: Vec<Vec<&HvpEntry>> = self
+            .index
+            .folded
+            .values()
+            .filter(|group| group.len() > 1)
+            .flat_map(|group| {
+                // Entries with a different parent collide through the parents.
+                let mut by_parent: HashMap<&[usize], Vec<&HvpEntry>> = HashMap::new();
+                for at in group {
+                    by_parent.entry(&at[..at.len() - 1]).or_default().push(resolve(&self.entries, at));
+                }
+                // Names that are the same byte for byte are duplicates, not
+                // collisions.
+                by_parent.into_values().filter(|entries| entries.iter().any(|entry| entry.path() != entries[0].path()))
+            })
+            .collect();
+        collisions.sort_by(|a, b| a[0].path().cmp(b[0].path()));
+        collisions
+    }
+}
+
+// Joins the non-empty components of `path` with `/`.
+pub(crate) fn normalize(path: &str) -> String {
+    path.split(['/', '\\']).filter(|name| !name.is_empty() && *name != ".").collect::<Vec<_>>().join("/")
+}
+
+pub(crate) fn resolve<'a>(entries: &'a [HvpEntry], at: &[usize]) -> &'a HvpEntry {
+    let (first, rest) = at.split_first().expect("index paths are not empty");
+    match (&entries[*first], rest) {
+        (HvpEntry::Directory(dir), [_, ..]) => resolve(&dir.children, rest),
+        (entry, _) => entry,
+    }
+}
+
+pub(crate) fn resolve_mut<'a>(entries: &'a mut [HvpEntry], at: &[usize]) -> &'a mut HvpEntry {
+    let (first, rest) = at.split_first().expect("index paths are not empty");
+    match (&mut entries[*first], rest) {
+        (HvpEntry::Directory(dir), [_, ..]) => resolve_mut(&mut dir.children, rest),
+        (entry, _) => entry,
+    }
+}
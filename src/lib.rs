@@ -0,0 +1,24 @@
+mod archive;
+mod edit;
+mod entry;
+mod error;
+mod index;
+mod manifest;
+mod mapped;
+mod name;
+mod reader;
+mod salvage;
+mod shared;
+mod verify;
+mod writer;
+
+pub use archive::{HvpArchive, ParseOptions};
+pub use entry::{is_safe_name, DirEntry, FileEntry, Header, HvpEntry, Walk};
+pub use error::{HvpError, Result};
+pub use manifest::{pack_manifest, raw_path, write_manifest};
+pub use mapped::map_file;
+pub use name::NameEncoding;
+pub use reader::EntryReader;
+pub use salvage::{salvage, Salvaged};
+pub use shared::SharedReader;
+pub use writer::pack_dir;
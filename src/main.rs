@@ -1,143 +1,68 @@
-use std::{
-    env::current_dir, error::Error, fs::{create_dir_all, File}, io::{Read, Write}, os::unix::fs::FileExt, path::{Path, PathBuf}, usize
-};
-use compress::zlib;
+use std::{error::Error, process::exit};
 
-static USAGE: &str = r#"
-Usage:
-extracthvp <in> [out]"#;
+mod cli;
 
-static TAG: &[u8] = "HV PackFile".as_bytes();
+use cli::{Args, CliResult};
 
-fn main() -> Result<(), Box<dyn Error>> {
+static USAGE: &str = r#"
+Usage:
+extracthvp <command> [--encoding auto|utf8|latin1|cp1252|lossy] [--max-entry-size <size>] [--max-total-size <size>] ...
+extracthvp [unpack] [-j <threads>] [--allow-unsafe-paths] [--raw-manifest <manifest>] [--include <glob>]... [--exclude <glob>]... [--regex <regex>] <in> [out]
+extracthvp cat <archive> <path in archive>
+extracthvp list [--tree] [--sort name|size|offset] <in>
+extracthvp pack [--store | --from-manifest <manifest>] <dir> <out>
+extracthvp replace <archive> <path in archive> <new file>
+extracthvp add [--store] <archive> <path in archive> <new file>
+extracthvp rm <archive> <path in archive>...
+extracthvp compact <archive>
+extracthvp test <archive>
+extracthvp salvage <archive> [out]"#;
+
+static EXTRACT_OPTIONS: &[&str] = &["--raw-manifest", "--include", "--exclude", "--regex", "-j"];
+
+fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
         println!("{}", USAGE);
-        return Ok(());
-    }
-    let in_file: &str = &args[1];
-    let out_dir = if args.len() > 2 { PathBuf::from(&args[2]) } else { current_dir().unwrap() };
-    let out_dir = Path::new(&out_dir);
-    if !out_dir.exists() {
-        println!("ERROR: Output directory {} does not exist!", out_dir.to_str().unwrap_or("unknown"));
-        return Ok(());
-    }
-
-    let mut hvp = File::open(in_file)?;
-    let mut buf = [0; 11];
-    let _ = hvp.read_exact(&mut buf);
-    if buf != TAG {
-        println!("ERROR: {} is not a valid HV PackFile", in_file);
-        return Ok(());
+        return;
     }
-    skip_bytes(&mut hvp, 5);
-    let n = read_integer(&mut hvp);
-    skip_bytes(&mut hvp, 20);
-    for _ in 0..n {
-        read_next(&mut hvp, out_dir);
+    if let Err(e) = run(&args[1], &args[2..]) {
+        // `cat` writes the entry to stdout, keep errors out of it.
+        if args[1] == "cat" {
+            eprintln!("ERROR: {}", e);
+        } else {
+            println!("ERROR: {}", e);
+        }
+        exit(1);
     }
-
-    Ok(())
 }
 
-fn read_next(file: &mut File, path: &Path) {
-    skip_bytes(file, 4);
-    let file_type = read_one(file);
-    if file_type != 0 {
-        read_file(file, path);
-    } else {
-        read_directory(file, path);
+fn run(command: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    match command {
+        "cat" => with_args(args, &[], &[], 2, cli::cat::run),
+        "compact" => with_args(args, &[], &[], 1, cli::compact::run),
+        "list" => with_args(args, &["--tree"], &["--sort"], 1, cli::list::run),
+        "pack" => with_args(args, &["--store"], &["--from-manifest"], 2, cli::pack::run),
+        "add" => with_args(args, &["--store"], &[], 3, cli::add::run),
+        "rm" => with_args(args, &[], &[], 2, cli::rm::run),
+        "replace" => with_args(args, &[], &[], 3, cli::replace::run),
+        "salvage" => with_args(args, &[], &[], 1, cli::salvage::run),
+        "test" => with_args(args, &[], &[], 1, cli::test::run),
+        "unpack" => with_args(args, &["--allow-unsafe-paths"], EXTRACT_OPTIONS, 1, cli::extract::run),
+        _ => {
+            let mut args = args.to_vec();
+            args.insert(0, command.to_string());
+            with_args(&args, &["--allow-unsafe-paths"], EXTRACT_OPTIONS, 1, cli::extract::run)
+        }
     }
 }
 
-// 4 - ???
-// 4 - no of files
-// 4 - length of the name
-// x - the name
-fn read_directory(file: &mut File, path: &Path) {
-    skip_bytes(file, 4);
-    let no_of_files = read_integer(file);
-    let name_length = read_integer(file);
-    let name = read_bytes(file, name_length.try_into().unwrap());
-    let name = String::from_utf8(name).unwrap();
-    let path = path.join(name);
-    create_dir(&path);
-    for _ in 0..no_of_files {
-        read_next(file, &path);
+fn with_args(args: &[String], flags: &[&str], with_value: &[&str], min_positional: usize, command: fn(&Args) -> CliResult) -> CliResult {
+    let with_value = [with_value, &["--encoding", "--max-entry-size", "--max-total-size"]].concat();
+    let args = Args::parse(args, flags, &with_value)?;
+    if args.positional.len() < min_positional {
+        eprintln!("{}", USAGE);
+        return Err("too few arguments".into());
     }
-}
-
-// 4 - 1 -> is compressed
-// 4 - the size of the compressed data
-// 4 - the size of the uncompressed data
-// 4 - ???
-// 4 - the offset from the start of the file where the data resides
-// 4 - length of the name
-// x - the name
-//
-fn read_file(file: &mut File, path: &Path) {
-    let is_compressed = read_integer(file);
-    let comp_size = read_integer(file);
-    let size = read_integer(file);
-    skip_bytes(file, 4);
-    let offset = read_integer(file);
-    let name_length = read_integer(file);
-    let name = read_bytes(file, name_length.try_into().unwrap());
-    let name = String::from_utf8(name).unwrap();
-    let data = if is_compressed != 0 {
-        read_compressed(file, offset, comp_size.try_into().unwrap(), size.try_into().unwrap())
-    } else {
-        read_uncompressed(file, offset, size.try_into().unwrap())
-    };
-    let path = path.join(name);
-    let mut out_file = create_file(&path);
-    _ = out_file.write_all(&data);
-}
-
-fn read_compressed(file: &mut File, offset: u32, comp_size: usize, size: usize) -> Vec<u8> {
-    let compressed = read_uncompressed(file, offset, comp_size);
-    let mut decompressed = vec![0; size];
-    _ = zlib::Decoder::new(compressed.as_slice()).read_to_end(&mut decompressed);
-    decompressed
-}
-
-fn read_uncompressed(file: &mut File, offset: u32, size: usize) -> Vec<u8> {
-    let mut buf = vec![0; size];
-    _ = file.read_exact_at(&mut buf, offset.into());
-    buf
-}
-
-fn create_dir(path: &Path) {
-    println!("Creating dir {}", path.display());
-    _ = create_dir_all(path.to_str().unwrap());
-}
-
-fn create_file(path: &Path) -> File {
-    println!("Creating file {}", path.to_str().unwrap_or(""));
-    File::create(path).unwrap()
-}
-
-fn skip_bytes(file: &mut File, bytes: usize) {
-    _ = read_bytes(file, bytes);
-}
-
-fn read_integer(file: &mut File) -> u32 {
-    u32::from_be_bytes(read_four(file))
-}
-
-fn read_one(file: &mut File) -> i32 {
-    let val = read_bytes(file, 1);
-    val[0].into()
-}
-
-fn read_four(file: &mut File) -> [u8; 4] {
-    let mut buf = [0; 4];
-    _ = file.read_exact(&mut buf);
-    buf
-}
-
-fn read_bytes(file: &mut File, bytes: usize) -> Vec<u8> {
-    let mut buf = vec![0; bytes];
-    _ = file.read_exact(&mut buf);
-    buf
+    command(&args)
 }
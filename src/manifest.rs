@@ -0,0 +1,326 @@
+use std::{
+    fs::File, io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}
+};
+
+use crate::archive::{decompress, HvpArchive};
+use crate::entry::{for_each_file_mut, join_path, DirEntry, FileEntry, Header, HvpEntry, Walk};
+use crate::error::{HvpError, Result};
+use crate::writer::{encode, table_len, to_u32, write_table};
+
+// A raw manifest is a text file describing everything extraction throws away:
+//
+// HVP-MANIFEST 2
+// header <5 bytes in hex> <20 bytes in hex>
+// size <length of the archive>
+// entries <number of top-level entries>
+// dir <prefix> <unknown> <no of files> <raw name in hex> <name>
+// file <prefix> <entry type> <compression flag> <comp size> <size> <unknown> <offset> <raw position or -> <raw name in hex> <name>
+// gap <offset> <length> <raw position>
+//
+// Entries are listed in archive order, each directory followed by its
+// children. The original compressed data and the bytes of every gap between
+// blobs are kept next to it in `<manifest>.raw`.
+static MAGIC: &str = "HVP-MANIFEST 2";
+
+struct Manifest {
+    header: Header,
+    size: u64,
+    entries: Vec<HvpEntry>,
+    /// Position in the raw file of each file's original data, in walk order.
+    raw: Vec<Option<u64>>,
+    gaps: Vec<Gap>,
+}
+
+struct Gap {
+    offset: u64,
+    len: u64,
+    raw: u64,
+}
+
+/// The path of the raw data file that accompanies `manifest`.
+pub fn raw_path(manifest: &Path) -> PathBuf {
+    let mut path = manifest.as_os_str().to_owned();
+    path.push(".raw");
+    PathBuf::from(path)
+}
+
+/// Writes a raw manifest of `archive` to `manifest`, which allows
+/// [`pack_manifest`] to rebuild the archive byte for byte.
+pub fn write_manifest<R: Read + Seek>(archive: &HvpArchive<R>, manifest: &Path) -> Result<()> {
+    let mut out = BufWriter::new(File::create(manifest)?);
+    let mut raw = BufWriter::new(File::create(raw_path(manifest))?);
+    let mut raw_pos = 0;
+
+    let header = archive.header();
+    writeln!(out, "{}", MAGIC)?;
+    writeln!(out, "header {} {}", to_hex(&header.unknown1), to_hex(&header.unknown2))?;
+    writeln!(out, "size {}", archive.size())?;
+    writeln!(out, "entries {}", archive.entries().len())?;
+    for entry in archive.walk() {
+        match entry {
+            HvpEntry::Directory(dir) => {
+                writeln!(
+                    out,
+                    "dir {:08x} {:08x} {} {} {}",
+                    dir.prefix,
+                    dir.unknown,
+                    dir.children.len(),
+                    to_hex(&dir.raw_name),
+                    dir.name
+                )?;
+            }
+            HvpEntry::File(file) => {
+                let position = if file.compressed {
+                    raw.write_all(&archive.read_raw(file)?)?;
+                    raw_pos += u64::from(file.comp_size);
+                    (raw_pos - u64::from(file.comp_size)).to_string()
+                } else {
+                    "-".to_string()
+                };
+                writeln!(
+                    out,
+                    "file {:08x} {:02x} {:08x} {} {} {:08x} {} {} {} {}",
+                    file.prefix,
+                    file.kind,
+                    file.compression,
+                    file.comp_size,
+                    file.size,
+                    file.unknown,
+                    file.offset,
+                    position,
+                    to_hex(&file.raw_name),
+                    file.name
+                )?;
+            }
+        }
+    }
+    for (offset, len) in gaps(archive.entries(), archive.size()) {
+        raw.write_all(&archive.read_at(offset, len as usize)?)?;
+        writeln!(out, "gap {} {} {}", offset, len, raw_pos)?;
+        raw_pos += len;
+    }
+    out.flush()?;
+    raw.flush()?;
+    Ok(())
+}
+
+/// Rebuilds the archive described by `manifest` from the files extracted to
+/// `dir`. Files that did not change are written from the raw data at their
+/// original offsets, so an untouched extraction reproduces the original
+/// archive exactly. Changed files are re-encoded and written in place if they
+/// still fit, or appended to the end of the archive otherwise.
+pub fn pack_manifest<W: Write + Seek>(manifest: &Path, dir: &Path, out: &mut W) -> Result<()> {
+    let Manifest { header, size, mut entries, raw, gaps } = read_manifest(manifest)?;
+    let mut raw_file = File::open(raw_path(manifest))?;
+
+    for gap in &gaps {
+        let data = read_raw(&mut raw_file, gap.raw, gap.len)?;
+        out.seek(SeekFrom::Start(gap.offset))?;
+        out.write_all(&data)?;
+    }
+
+    let mut raw = raw.into_iter();
+    let mut end = size;
+    for_each_file_mut(&mut entries, &mut |file| {
+        let mut data = Vec::new();
+        File::open(dir.join(&file.path))?.read_to_end(&mut data)?;
+        let room = file.stored_len() as usize;
+        let blob = match raw.next().flatten() {
+            Some(position) => {
+                let original = read_raw(&mut raw_file, position, file.comp_size.into())?;
+                if decompress(original.as_slice(), file)? == data {
+                    original
+                } else {
+                    let blob = encode(&data)?;
+                    file.compressed = blob.is_some();
+                    file.size = to_u32(data.len() as u64, &file.path)?;
+                    blob.unwrap_or(data)
+                }
+            }
+            None => {
+                file.size = to_u32(data.len() as u64, &file.path)?;
+                data
+            }
+        };
+        if blob.len() > room {
+            file.offset = to_u32(end, &file.path)?;
+            end += blob.len() as u64;
+        }
+        file.comp_size = to_u32(blob.len() as u64, &file.path)?;
+        out.seek(SeekFrom::Start(file.offset.into()))?;
+        out.write_all(&blob)?;
+        Ok::<_, HvpError>(())
+    })?;
+
+    out.rewind()?;
+    write_table(out, &header, &entries)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The regions of an archive of `size` bytes that lie after the entry records
+/// and are not covered by the data of any file, as `(offset, length)` pairs.
+fn gaps(entries: &[HvpEntry], size: u64) -> Vec<(u64, u64)> {
+    let mut blobs: Vec<(u64, u64)> = Walk::new(entries)
+        .filter_map(|entry| match entry {
+            HvpEntry::File(file) => Some((file.offset.into(), u64::from(file.offset) + u64::from(file.stored_len()))),
+            HvpEntry::Directory(_) => None,
+        })
+        .collect();
+    blobs.sort();
+
+    let mut gaps = Vec::new();
+    let mut pos = table_len(entries);
+    for (start, end) in blobs {
+        if start > pos {
+            gaps.push((pos, start - pos));
+        }
+        pos = pos.max(end);
+    }
+    if size > pos {
+        gaps.push((pos, size - pos));
+    }
+    gaps
+}
+
+fn read_raw(raw: &mut File, position: u64, len: u64) -> Result<Vec<u8>> {
+    let mut buf = vec![0; len as usize];
+    raw.seek(SeekFrom::Start(position))?;
+    raw.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest> {
+    let mut lines = Lines { lines: BufReader::new(File::open(path)?).lines(), number: 0 };
+    if lines.next()? != MAGIC {
+        return Err(HvpError::BadManifest { line: lines.number });
+    }
+    let mut header = Header::default();
+    let fields = lines.fields("header", 3)?;
+    from_hex(&fields[1], &mut header.unknown1).ok_or(lines.error())?;
+    from_hex(&fields[2], &mut header.unknown2).ok_or(lines.error())?;
+    let size = lines.number_field("size")?;
+    let count = lines.number_field("entries")?;
+
+    let mut raw = Vec::new();
+    let entries = read_entries(&mut lines, count, "", &mut raw)?;
+    let mut gaps = Vec::new();
+    while let Some(line) = lines.next_opt()? {
+        let fields: Vec<&str> = line.split(' ').collect();
+        if fields.len() != 4 || fields[0] != "gap" {
+            return Err(lines.error());
+        }
+        gaps.push(Gap { offset: lines.parse(fields[1])?, len: lines.parse(fields[2])?, raw: lines.parse(fields[3])? });
+    }
+    Ok(Manifest { header, size, entries, raw, gaps })
+}
+
+fn read_entries(lines: &mut Lines, count: u64, parent: &str, raw: &mut Vec<Option<u64>>) -> Result<Vec<HvpEntry>> {
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let line = lines.next()?;
+        if line.starts_with("dir ") {
+            let fields: Vec<&str> = line.splitn(6, ' ').collect();
+            if fields.len() != 6 {
+                return Err(lines.error());
+            }
+            let name = fields[5].to_string();
+            let raw_name = lines.parse_bytes(fields[4])?;
+            let path = join_path(parent, &name);
+            let prefix = lines.parse_hex(fields[1])?;
+            let unknown = lines.parse_hex(fields[2])?;
+            let children = read_entries(lines, lines.parse(fields[3])?, &path, raw)?;
+            entries.push(HvpEntry::Directory(DirEntry { name, raw_name, path, prefix, unknown, children }));
+        } else if line.starts_with("file ") {
+            let fields: Vec<&str> = line.splitn(11, ' ').collect();
+            if fields.len() != 11 {
+                return Err(lines.error());
+            }
+            let name = fields[10].to_string();
+            let kind = u8::from_str_radix(fields[2], 16).map_err(|_| lines.error())?;
+            let compression = lines.parse_hex(fields[3])?;
+            if kind == 0 {
+                return Err(lines.error());
+            }
+            raw.push(if fields[8] == "-" { None } else { Some(lines.parse(fields[8])?) });
+            entries.push(HvpEntry::File(FileEntry {
+                path: join_path(parent, &name),
+                name,
+                raw_name: lines.parse_bytes(fields[9])?,
+                prefix: lines.parse_hex(fields[1])?,
+                kind,
+                compressed: compression != 0,
+                compression,
+                comp_size: lines.parse(fields[4])?,
+                size: lines.parse(fields[5])?,
+                unknown: lines.parse_hex(fields[6])?,
+                offset: lines.parse(fields[7])?,
+            }));
+        } else {
+            return Err(lines.error());
+        }
+    }
+    Ok(entries)
+}
+
+struct Lines {
+    lines: std::io::Lines<BufReader<File>>,
+    number: usize,
+}
+
+impl Lines {
+    fn next_opt(&mut self) -> Result<Option<String>> {
+        self.number += 1;
+        Ok(self.lines.next().transpose()?)
+    }
+
+    fn next(&mut self) -> Result<String> {
+        self.next_opt()?.ok_or(self.error())
+    }
+
+    fn fields(&mut self, key: &str, count: usize) -> Result<Vec<String>> {
+        let line = self.next()?;
+        let fields: Vec<String> = line.split(' ').map(str::to_string).collect();
+        if fields.len() != count || fields[0] != key {
+            return Err(self.error());
+        }
+        Ok(fields)
+    }
+
+    fn number_field<T: std::str::FromStr>(&mut self, key: &str) -> Result<T> {
+        let fields = self.fields(key, 2)?;
+        self.parse(&fields[1])
+    }
+
+    fn parse<T: std::str::FromStr>(&self, field: &str) -> Result<T> {
+        field.parse().map_err(|_| self.error())
+    }
+
+    fn parse_bytes(&self, field: &str) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; field.len() / 2];
+        from_hex(field, &mut bytes).ok_or(self.error())?;
+        Ok(bytes)
+    }
+
+    fn parse_hex(&self, field: &str) -> Result<u32> {
+        u32::from_str_radix(field, 16).map_err(|_| self.error())
+    }
+
+    fn error(&self) -> HvpError {
+        HvpError::BadManifest { line: self.number }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str, out: &mut [u8]) -> Option<()> {
+    if hex.len() != out.len() * 2 {
+        return None;
+    }
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(())
+}
@@ -0,0 +1,69 @@
+use std::{
+    borrow::Cow, fs::File, io::Cursor, path::Path
+};
+use memmap2::Mmap;
+
+use crate::archive::{parse, HvpArchive, ParseOptions, Source};
+use crate::entry::FileEntry;
+use crate::error::{HvpError, Result};
+
+/// Maps the archive at `path` into memory, to be parsed with
+/// [`HvpArchive::from_slice`]. The file must not be changed while it is mapped.
+pub fn map_file<P: AsRef<Path>>(path: P) -> Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: the archive is only read, changing the file underneath the
+    // mapping is ruled out by the documented contract of this function.
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+impl<'a> HvpArchive<Cursor<&'a [u8]>> {
+    /// Parses an archive held in memory, e.g. mapped with [`map_file`]. The
+    /// fields of the entry records are read straight from `data`, only the
+    /// names are copied into the entries.
+    pub fn from_slice(data: &'a [u8], options: &ParseOptions) -> Result<Self> {
+        let (header, entries) = parse(Slice { data, pos: 0 }, data.len() as u64, options)?;
+        Ok(HvpArchive::from_parts(Cursor::new(data), data.len() as u64, header, entries, options))
+    }
+
+    /// The data of `entry` exactly as stored in the archive, without copying
+    /// it. For uncompressed files these are their contents.
+    pub fn blob(&self, entry: &FileEntry) -> Result<&'a [u8]> {
+        self.check_bounds(entry)?;
+        let data: &'a [u8] = self.file.borrow().get_ref();
+        let start = entry.offset as usize;
+        Ok(&data[start..start + entry.stored_len() as usize])
+    }
+}
+
+// An archive in memory, read from `pos` on.
+struct Slice<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Slice<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self.data.get(self.pos..).and_then(|rest| rest.get(..len));
+        let bytes = bytes.ok_or(HvpError::Truncated { offset: self.pos as u64 })?;
+        self.pos += len;
+        Ok(bytes)
+    }
+}
+
+impl Source for Slice<'_> {
+    fn position(&mut self) -> Result<u64> {
+        Ok(self.pos as u64)
+    }
+
+    fn read_integer(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().expect("took four bytes")))
+    }
+
+    fn read_one(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.take(len)?))
+    }
+}
@@ -0,0 +1,77 @@
+/// How entry names are decoded from the bytes stored in the archive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameEncoding {
+    /// UTF-8 if the name is valid UTF-8, otherwise Windows-1252, falling back
+    /// to Latin-1 for the bytes Windows-1252 leaves undefined.
+    #[default]
+    Auto,
+    Utf8,
+    Latin1,
+    Cp1252,
+    /// UTF-8, replacing invalid sequences with U+FFFD.
+    Lossy,
+}
+
+// Windows-1252 characters for 0x80 to 0x9f, '\0' where undefined.
+static CP1252: [char; 32] = [
+    '\u{20ac}', '\0', '\u{201a}', '\u{0192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02c6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\0', '\u{017d}', '\0',
+    '\0', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02dc}', '\u{2122}', '\u{0161}', '\u{203a}', '\u{0153}', '\0', '\u{017e}', '\u{0178}',
+];
+
+impl NameEncoding {
+    /// Decodes `bytes`, returning `None` if they are not valid in this
+    /// encoding.
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            NameEncoding::Auto => NameEncoding::Utf8
+                .decode(bytes)
+                .or_else(|| Some(bytes.iter().map(|&b| cp1252_char(b).unwrap_or(char::from(b))).collect())),
+            NameEncoding::Utf8 => String::from_utf8(bytes.to_vec()).ok(),
+            NameEncoding::Latin1 => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+            NameEncoding::Cp1252 => bytes.iter().map(|&b| cp1252_char(b)).collect(),
+            NameEncoding::Lossy => Some(String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+
+    /// Encodes `name` for writing it to an archive, returning `None` if it
+    /// holds characters the encoding cannot represent. Names are written as
+    /// UTF-8 unless Latin-1 or Windows-1252 is chosen explicitly.
+    pub fn encode(self, name: &str) -> Option<Vec<u8>> {
+        match self {
+            NameEncoding::Auto | NameEncoding::Utf8 | NameEncoding::Lossy => Some(name.as_bytes().to_vec()),
+            NameEncoding::Latin1 => name.chars().map(|c| u8::try_from(c).ok()).collect(),
+            NameEncoding::Cp1252 => name.chars().map(cp1252_byte).collect(),
+        }
+    }
+}
+
+impl std::str::FromStr for NameEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(NameEncoding::Auto),
+            "utf8" | "utf-8" => Ok(NameEncoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(NameEncoding::Latin1),
+            "cp1252" | "windows-1252" => Ok(NameEncoding::Cp1252),
+            "lossy" => Ok(NameEncoding::Lossy),
+            _ => Err(format!("unknown name encoding {}, expected auto, utf8, latin1, cp1252 or lossy", s)),
+        }
+    }
+}
+
+fn cp1252_char(b: u8) -> Option<char> {
+    match b {
+        0x80..=0x9f => Some(CP1252[usize::from(b - 0x80)]).filter(|&c| c != '\0'),
+        _ => Some(char::from(b)),
+    }
+}
+
+fn cp1252_byte(c: char) -> Option<u8> {
+    match u8::try_from(c) {
+        Ok(b) if !(0x80..=0x9f).contains(&b) => Some(b),
+        _ => CP1252.iter().position(|&x| x == c && c != '\0').map(|i| 0x80 + i as u8),
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs::File, io::{self, BufReader, Read, Seek, SeekFrom}
+};
+use compress::zlib;
+
+use crate::archive::{check_limits, HvpArchive};
+use crate::entry::FileEntry;
+use crate::error::{HvpError, Result};
+
+/// Streams the contents of one file of an archive. Compressed files are
+/// inflated as they are read, seeking back in them starts inflating over from
+/// the beginning.
+pub struct EntryReader<'a, R = File> {
+    archive: &'a HvpArchive<R>,
+    entry: &'a FileEntry,
+    pos: u64,
+    /// The decoder of a compressed file and how many bytes it has inflated.
+    decoder: Option<(zlib::Decoder<BufReader<Stored<'a, R>>>, u64)>,
+}
+
+impl<R: Read + Seek> HvpArchive<R> {
+    /// Opens the file at `path` for reading without extracting it.
+    pub fn open_entry(&self, path: &str) -> Result<EntryReader<'_, R>> {
+        let entry = self.find(path).ok_or_else(|| HvpError::NotFound { path: path.to_string() })?;
+        self.reader(entry)
+    }
+
+    /// Opens `entry` for reading without extracting it. Checks the same
+    /// limits as [`HvpArchive::read`], the whole entry counts as read.
+    pub fn reader<'a>(&'a self, entry: &'a FileEntry) -> Result<EntryReader<'a, R>> {
+        self.check_bounds(entry)?;
+        check_limits(&self.options, self.total_read.get(), entry)?;
+        self.total_read.set(self.total_read.get() + u64::from(entry.size));
+        Ok(EntryReader { archive: self, entry, pos: 0, decoder: None })
+    }
+}
+
+impl<'a, R: Read + Seek> EntryReader<'a, R> {
+    pub fn entry(&self) -> &'a FileEntry {
+        self.entry
+    }
+
+    fn stored(&self, pos: u64) -> Stored<'a, R> {
+        let offset = u64::from(self.entry.offset);
+        Stored { archive: self.archive, pos: offset + pos, end: offset + u64::from(self.entry.stored_len()) }
+    }
+
+    fn inflate(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let pos = self.pos;
+        if self.decoder.as_ref().is_none_or(|(_, decoded)| *decoded > pos) {
+            self.decoder = Some((zlib::Decoder::new(BufReader::new(self.stored(0))), 0));
+        }
+        let (decoder, decoded) = self.decoder.as_mut().expect("decoder was just set");
+        if *decoded < pos {
+            *decoded += io::copy(&mut (&mut *decoder).take(pos - *decoded), &mut io::sink())?;
+        }
+        let n = if *decoded < pos { 0 } else { decoder.read(buf)? };
+        *decoded += n as u64;
+        if n == 0 {
+            let e = HvpError::SizeMismatch { entry: self.entry.path.clone(), expected: self.entry.size.into(), actual: *decoded };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Read for EntryReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = u64::from(self.entry.size).saturating_sub(self.pos);
+        let len = left.min(buf.len() as u64) as usize;
+        let buf = &mut buf[..len];
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let n = if self.entry.compressed { self.inflate(buf)? } else { self.stored(self.pos).read(buf)? };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for EntryReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => u64::from(self.entry.size).checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position"))?;
+        Ok(self.pos)
+    }
+}
+
+// The stored data of an entry from `pos` up to `end`, read through the
+// archive's reader.
+struct Stored<'a, R> {
+    archive: &'a HvpArchive<R>,
+    pos: u64,
+    end: u64,
+}
+
+impl<R: Read + Seek> Read for Stored<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.end.saturating_sub(self.pos).min(buf.len() as u64) as usize;
+        let file = &mut *self.archive.file.borrow_mut();
+        file.seek(SeekFrom::Start(self.pos))?;
+        let n = file.read(&mut buf[..len])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
@@ -0,0 +1,174 @@
+use flate2::{Decompress, FlushDecompress, Status};
+
+use crate::archive::ParseOptions;
+use crate::entry::{is_safe_name, join_path};
+use crate::error::{HvpError, Result};
+use crate::name::NameEncoding;
+use crate::writer::HEADER_LEN;
+
+/// A piece of data recovered by [`salvage`].
+#[derive(Debug, Clone)]
+pub struct Salvaged {
+    /// Where the data starts in the archive.
+    pub offset: u64,
+    /// The path of the entry the data belongs to, if its record survived.
+    pub path: Option<String>,
+    pub data: Vec<u8>,
+}
+
+// A file record that could still be parsed.
+struct Record {
+    path: String,
+    compressed: bool,
+    comp_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Recovers what it can from an archive with damaged entry records. Files
+/// whose records still parse are read through them, then the rest of the
+/// archive is scanned for zlib streams, which are inflated and reported
+/// without a path. `f` is called with each piece of recovered data in turn.
+/// Streams inflating to more than `options.max_entry_size` are skipped.
+pub fn salvage<F: FnMut(Salvaged) -> Result<()>>(archive: &[u8], options: &ParseOptions, mut f: F) -> Result<()> {
+    let mut total = 0;
+    let mut f = |item: Salvaged| {
+        total += item.data.len() as u64;
+        if total > options.max_total_size {
+            return Err(HvpError::TotalTooLarge { limit: options.max_total_size });
+        }
+        f(item)
+    };
+    let mut covered = Vec::new();
+    let mut records = surviving_records(archive, options.encoding);
+    records.sort_by_key(|record| record.offset);
+    for record in records {
+        let start = record.offset as usize;
+        let len = if record.compressed { record.comp_size } else { record.size };
+        let Some(blob) = archive.get(start..start + len as usize) else {
+            continue;
+        };
+        if u64::from(record.size) > options.max_entry_size {
+            continue;
+        }
+        let data = if record.compressed {
+            match inflate(blob, options.max_entry_size) {
+                Some((data, _)) => data,
+                None => continue,
+            }
+        } else {
+            blob.to_vec()
+        };
+        if data.len() != record.size as usize {
+            continue;
+        }
+        covered.push(start..start + blob.len());
+        f(Salvaged { offset: record.offset.into(), path: Some(record.path), data })?;
+    }
+
+    // The covered ranges are sorted by their start, so the scan only has to
+    // look at the first one that does not end before it.
+    let mut covered = covered.into_iter().peekable();
+    let mut pos = HEADER_LEN as usize;
+    while pos + 2 <= archive.len() {
+        while covered.next_if(|range| range.end <= pos).is_some() {}
+        if let Some(range) = covered.peek().filter(|range| range.start <= pos) {
+            pos = range.end;
+            continue;
+        }
+        if is_zlib_header(archive[pos], archive[pos + 1]) {
+            if let Some((data, len)) = inflate(&archive[pos..], options.max_entry_size) {
+                if !data.is_empty() {
+                    f(Salvaged { offset: pos as u64, path: None, data })?;
+                }
+                pos += len;
+                continue;
+            }
+        }
+        pos += 1;
+    }
+    Ok(())
+}
+
+fn is_zlib_header(cmf: u8, flg: u8) -> bool {
+    // Deflate with a window of at most 32K and no preset dictionary.
+    cmf & 0x0f == 8 && cmf >> 4 <= 7 && flg & 0x20 == 0 && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0
+}
+
+// Inflates the zlib stream at the start of `blob`, giving up once it grows
+// past `limit` bytes. Returns the data and the length of the stream.
+fn inflate(blob: &[u8], limit: u64) -> Option<(Vec<u8>, usize)> {
+    let mut inflater = Decompress::new(true);
+    let mut data = Vec::new();
+    loop {
+        data.reserve(32 * 1024);
+        let (read, written) = (inflater.total_in(), inflater.total_out());
+        let status = inflater.decompress_vec(&blob[read as usize..], &mut data, FlushDecompress::None).ok()?;
+        if data.len() as u64 > limit {
+            return None;
+        }
+        if status == Status::StreamEnd {
+            return Some((data, inflater.total_in() as usize));
+        }
+        if inflater.total_in() == read && inflater.total_out() == written {
+            // The stream is cut short.
+            return None;
+        }
+    }
+}
+
+// Reads entry records in order until the first one that does not make sense,
+// returning the files among them whose paths are safe to extract to.
+fn surviving_records(archive: &[u8], encoding: NameEncoding) -> Vec<Record> {
+    let mut records = Vec::new();
+    let Some(count) = read_integer(archive, 16) else {
+        return records;
+    };
+    // The directories the next record belongs to and how many entries they
+    // still hold.
+    let mut stack = vec![(String::new(), count)];
+    let mut pos = HEADER_LEN as usize;
+    loop {
+        while stack.last().is_some_and(|(_, remaining)| *remaining == 0) {
+            stack.pop();
+        }
+        let Some((parent, remaining)) = stack.last_mut() else {
+            break;
+        };
+        *remaining -= 1;
+        let parent = parent.clone();
+        let Some(&file_type) = archive.get(pos + 4) else {
+            break;
+        };
+        let fields = if file_type != 0 { 6 } else { 3 };
+        let Some(values) = (0..fields).map(|i| read_integer(archive, pos + 5 + i * 4)).collect::<Option<Vec<u32>>>() else {
+            break;
+        };
+        let name_start = pos + 5 + fields * 4;
+        let name_length = values[fields - 1] as usize;
+        let Some(name) = archive.get(name_start..name_start + name_length).and_then(|name| encoding.decode(name)) else {
+            break;
+        };
+        if !is_safe_name(&name) {
+            break;
+        }
+        let path = join_path(&parent, &name);
+        pos = name_start + name_length;
+        if file_type != 0 {
+            records.push(Record {
+                path,
+                compressed: values[0] != 0,
+                comp_size: values[1],
+                size: values[2],
+                offset: values[4],
+            });
+        } else {
+            stack.push((path, values[1]));
+        }
+    }
+    records
+}
+
+fn read_integer(archive: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(archive.get(pos..pos + 4)?.try_into().ok()?))
+}
@@ -0,0 +1,87 @@
+use std::{
+    fs::File, io::{self, Read, Write}, sync::atomic::{AtomicU64, Ordering}
+};
+
+use crate::archive::{check_bounds, check_limits, copy_entry, HvpArchive, ParseOptions};
+use crate::entry::FileEntry;
+use crate::error::Result;
+
+/// Reads the entries of an archive file from several threads at once. Every
+/// read is positional, so the threads never share a cursor.
+pub struct SharedReader {
+    file: File,
+    len: u64,
+    options: ParseOptions,
+    total_read: AtomicU64,
+}
+
+impl HvpArchive<File> {
+    /// A reader for the entries of this archive that can be shared between
+    /// threads. Its size limits count what the archive has read so far.
+    pub fn shared_reader(&self) -> Result<SharedReader> {
+        Ok(SharedReader {
+            file: self.file.borrow().try_clone()?,
+            len: self.len,
+            options: self.options.clone(),
+            total_read: AtomicU64::new(self.total_read.get()),
+        })
+    }
+}
+
+impl SharedReader {
+    /// Like [`HvpArchive::read`].
+    pub fn read(&self, entry: &FileEntry) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.read_to(entry, &mut data)?;
+        Ok(data)
+    }
+
+    /// Like [`HvpArchive::read_to`].
+    pub fn read_to<W: Write>(&self, entry: &FileEntry, out: &mut W) -> Result<u64> {
+        check_bounds(self.len, entry)?;
+        // Claim the entry's share of the total up front, other threads may
+        // be reading at the same time.
+        let total = self.total_read.fetch_add(entry.size.into(), Ordering::Relaxed);
+        if let Err(e) = check_limits(&self.options, total, entry) {
+            // Nothing is read, give the claim back.
+            self.total_read.fetch_sub(entry.size.into(), Ordering::Relaxed);
+            return Err(e);
+        }
+        copy_entry(ReadAt { file: &self.file, pos: entry.offset.into() }, entry, out)
+    }
+}
+
+// Reads `file` from `pos` on without moving its cursor.
+struct ReadAt<'a> {
+    file: &'a File,
+    pos: u64,
+}
+
+impl Read for ReadAt<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = read_at(self.file, buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, pos)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, pos)
+}
+
+// Elsewhere there are no positional reads and the cursor of `file` is shared
+// with its clones, so every read seeks and reads under one lock.
+#[cfg(not(any(unix, windows)))]
+fn read_at(mut file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    use std::{io::{Seek, SeekFrom}, sync::Mutex};
+    static CURSOR: Mutex<()> = Mutex::new(());
+    let _cursor = CURSOR.lock().unwrap_or_else(|e| e.into_inner());
+    file.seek(SeekFrom::Start(pos))?;
+    file.read(buf)
+}
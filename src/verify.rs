@@ -0,0 +1,27 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::archive::{copy_entry, HvpArchive};
+use crate::entry::FileEntry;
+use crate::error::{HvpError, Result};
+
+impl<R: Read + Seek> HvpArchive<R> {
+    /// Checks that the data of `file` lies inside the archive and, if it is
+    /// compressed, inflates to the recorded size. The data is inflated a chunk
+    /// at a time and thrown away.
+    pub fn verify_file(&self, file: &FileEntry) -> Result<()> {
+        self.check_bounds(file)?;
+        if !file.compressed {
+            return Ok(());
+        }
+        let stored = &mut *self.file.borrow_mut();
+        stored.seek(SeekFrom::Start(file.offset.into()))?;
+        copy_entry(stored, file, &mut io::sink())?;
+        Ok(())
+    }
+
+    /// Runs [`HvpArchive::verify_file`] on every file, returning the ones
+    /// that failed.
+    pub fn verify(&self) -> Vec<(&FileEntry, HvpError)> {
+        self.files().filter_map(|file| self.verify_file(file).err().map(|e| (file, e))).collect()
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fs::{self, File}, io::{self, Read, Seek, SeekFrom, Write}, path::Path
+};
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::archive::TAG;
+use crate::entry::{join_path, DirEntry, FileEntry, Header, HvpEntry, Walk};
+use crate::error::{HvpError, Result};
+use crate::name::NameEncoding;
+
+// 11 - "HV PackFile"
+// 5  - ???
+// 4  - number of top-level entries
+// 20 - ???
+pub(crate) const HEADER_LEN: u64 = 40;
+
+/// Packs the contents of `dir` into a new archive written to `out`. Files are
+/// zlib compressed when `compress` is set and compression makes them smaller,
+/// otherwise they are stored as-is. Names are written in `encoding`.
+pub fn pack_dir<W: Write + Seek>(dir: &Path, out: &mut W, compress: bool, encoding: NameEncoding) -> Result<()> {
+    let mut entries = scan_dir(dir, "", encoding)?;
+    let mut offset = table_len(&entries);
+    out.seek(SeekFrom::Start(offset))?;
+    write_blobs(dir, out, &mut entries, &mut offset, compress)?;
+    out.rewind()?;
+    write_table(out, &Header::default(), &entries)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The length of the header and all entry records, i.e. where the data of
+/// the first file may start.
+pub(crate) fn table_len(entries: &[HvpEntry]) -> u64 {
+    HEADER_LEN + entries.iter().map(record_len).sum::<u64>()
+}
+
+/// The position of the record of the entry at `path`.
+pub(crate) fn record_offset(entries: &[HvpEntry], path: &str) -> Option<u64> {
+    let mut offset = HEADER_LEN;
+    for entry in Walk::new(entries) {
+        if entry.path() == path {
+            return Some(offset);
+        }
+        offset += own_record_len(entry);
+    }
+    None
+}
+
+/// Writes the header and all entry records in the layout `HvpArchive::new`
+/// parses.
+pub(crate) fn write_table<W: Write>(out: &mut W, header: &Header, entries: &[HvpEntry]) -> Result<()> {
+    out.write_all(TAG)?;
+    out.write_all(&header.unknown1)?;
+    write_integer(out, to_u32(entries.len() as u64, "")?)?;
+    out.write_all(&header.unknown2)?;
+    for entry in entries {
+        write_entry(out, entry)?;
+    }
+    Ok(())
+}
+
+fn scan_dir(dir: &Path, parent: &str, encoding: NameEncoding) -> Result<Vec<HvpEntry>> {
+    let mut names = Vec::new();
+    for item in fs::read_dir(dir)? {
+        names.push(item?.file_name());
+    }
+    names.sort();
+
+    let mut entries = Vec::new();
+    for name in names {
+        let path = dir.join(&name);
+        let name = name.into_string().map_err(|name| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a valid entry name", name.to_string_lossy()))
+        })?;
+        let raw_name = encode_name(&name, encoding)?;
+        let entry_path = join_path(parent, &name);
+        if path.is_dir() {
+            let children = scan_dir(&path, &entry_path, encoding)?;
+            entries.push(HvpEntry::Directory(DirEntry { name, raw_name, path: entry_path, prefix: 0, unknown: 0, children }));
+        } else {
+            entries.push(HvpEntry::File(FileEntry {
+                name,
+                raw_name,
+                path: entry_path,
+                prefix: 0,
+                kind: 1,
+                compressed: false,
+                compression: 0,
+                comp_size: 0,
+                size: 0,
+                unknown: 0,
+                offset: 0,
+            }));
+        }
+    }
+    Ok(entries)
+}
+
+fn write_blobs<W: Write>(dir: &Path, out: &mut W, entries: &mut [HvpEntry], offset: &mut u64, compress: bool) -> Result<()> {
+    for entry in entries {
+        match entry {
+            HvpEntry::Directory(sub) => write_blobs(dir, out, &mut sub.children, offset, compress)?,
+            HvpEntry::File(file) => {
+                let mut data = Vec::new();
+                File::open(dir.join(&file.path))?.read_to_end(&mut data)?;
+                file.size = to_u32(data.len() as u64, &file.path)?;
+                let blob = if compress { encode(&data)? } else { None };
+                file.compressed = blob.is_some();
+                let blob = blob.unwrap_or(data);
+                file.comp_size = to_u32(blob.len() as u64, &file.path)?;
+                file.offset = to_u32(*offset, &file.path)?;
+                out.write_all(&blob)?;
+                *offset += blob.len() as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compresses `data`, returning `None` if that would not make it smaller.
+pub(crate) fn encode(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    let blob = encoder.finish()?;
+    Ok(if blob.len() < data.len() { Some(blob) } else { None })
+}
+
+pub(crate) fn encode_name(name: &str, encoding: NameEncoding) -> Result<Vec<u8>> {
+    encoding.encode(name).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} cannot be encoded as {:?}", name, encoding)).into()
+    })
+}
+
+pub(crate) fn to_u32(value: u64, entry: &str) -> Result<u32> {
+    value.try_into().map_err(|_| HvpError::TooLarge { entry: entry.to_string() })
+}
+
+// Mirrors read_next, read_directory and read_file.
+fn write_entry<W: Write>(out: &mut W, entry: &HvpEntry) -> Result<()> {
+    match entry {
+        HvpEntry::Directory(dir) => {
+            write_integer(out, dir.prefix)?;
+            out.write_all(&[0])?;
+            write_integer(out, dir.unknown)?;
+            write_integer(out, to_u32(dir.children.len() as u64, &dir.path)?)?;
+            write_name(out, &dir.raw_name)?;
+            for child in &dir.children {
+                write_entry(out, child)?;
+            }
+        }
+        HvpEntry::File(file) => {
+            write_integer(out, file.prefix)?;
+            out.write_all(&[file.kind])?;
+            write_file_fields(out, file)?;
+            write_name(out, &file.raw_name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the fields of a file record between the entry type and the name.
+pub(crate) fn write_file_fields<W: Write>(out: &mut W, file: &FileEntry) -> io::Result<()> {
+    write_integer(out, compression_flag(file))?;
+    write_integer(out, file.comp_size)?;
+    write_integer(out, file.size)?;
+    write_integer(out, file.unknown)?;
+    write_integer(out, file.offset)
+}
+
+// The stored compression flag, unless `compressed` was changed since.
+fn compression_flag(file: &FileEntry) -> u32 {
+    if (file.compression != 0) == file.compressed {
+        file.compression
+    } else {
+        file.compressed.into()
+    }
+}
+
+fn record_len(entry: &HvpEntry) -> u64 {
+    match entry {
+        HvpEntry::Directory(dir) => own_record_len(entry) + dir.children.iter().map(record_len).sum::<u64>(),
+        HvpEntry::File(_) => own_record_len(entry),
+    }
+}
+
+// The length of the record of `entry` alone, without its children.
+fn own_record_len(entry: &HvpEntry) -> u64 {
+    match entry {
+        HvpEntry::Directory(dir) => 5 + 12 + dir.raw_name.len() as u64,
+        HvpEntry::File(file) => 5 + 24 + file.raw_name.len() as u64,
+    }
+}
+
+fn write_name<W: Write>(out: &mut W, name: &[u8]) -> Result<()> {
+    write_integer(out, to_u32(name.len() as u64, &String::from_utf8_lossy(name))?)?;
+    out.write_all(name)?;
+    Ok(())
+}
+
+fn write_integer<W: Write>(out: &mut W, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_be_bytes())
+}
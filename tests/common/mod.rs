@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+
+use std::{
+    fs, io::Write, path::{Path, PathBuf}
+};
+use flate2::{write::ZlibEncoder, Compression};
+
+/// Builds an archive by hand, record by record. The data of the files is laid
+/// out after the entry records in the order they were added.
+pub struct ArchiveBuilder {
+    count: u32,
+    records: Vec<u8>,
+    /// Where each file's offset field is in `records`, with its data.
+    blobs: Vec<(usize, Vec<u8>)>,
+}
+
+impl ArchiveBuilder {
+    /// An archive announcing `count` top-level entries.
+    pub fn new(count: u32) -> ArchiveBuilder {
+        ArchiveBuilder { count, records: Vec::new(), blobs: Vec::new() }
+    }
+
+    /// A directory record announcing `count` children, which are the records
+    /// added next.
+    pub fn dir(mut self, name: &str, count: u32) -> ArchiveBuilder {
+        self.records.extend_from_slice(&[0; 4]);
+        self.records.push(0);
+        for field in [0, count, name.len() as u32] {
+            self.records.extend_from_slice(&field.to_be_bytes());
+        }
+        self.records.extend_from_slice(name.as_bytes());
+        self
+    }
+
+    /// A stored file holding `data`.
+    pub fn file(self, name: &str, data: &[u8]) -> ArchiveBuilder {
+        self.file_with(name, 0, data.len() as u32, data.len() as u32, data)
+    }
+
+    /// A zlib compressed file holding `data`.
+    pub fn compressed(self, name: &str, data: &[u8]) -> ArchiveBuilder {
+        let blob = zlib(data);
+        self.file_with(name, 1, blob.len() as u32, data.len() as u32, &blob)
+    }
+
+    /// A file record with the given fields, whatever `blob` holds.
+    pub fn file_with(mut self, name: &str, compression: u32, comp_size: u32, size: u32, blob: &[u8]) -> ArchiveBuilder {
+        self.records.extend_from_slice(&[0; 4]);
+        self.records.push(1);
+        for field in [compression, comp_size, size, 0] {
+            self.records.extend_from_slice(&field.to_be_bytes());
+        }
+        self.blobs.push((self.records.len(), blob.to_vec()));
+        for field in [0, name.len() as u32] {
+            self.records.extend_from_slice(&field.to_be_bytes());
+        }
+        self.records.extend_from_slice(name.as_bytes());
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let mut hvp = Vec::new();
+        hvp.extend_from_slice(b"HV PackFile");
+        hvp.extend_from_slice(&[0; 5]);
+        hvp.extend_from_slice(&self.count.to_be_bytes());
+        hvp.extend_from_slice(&[0; 20]);
+        let table = hvp.len();
+        hvp.extend_from_slice(&self.records);
+        for (field, blob) in self.blobs {
+            let offset = hvp.len() as u32;
+            hvp[table + field..table + field + 4].copy_from_slice(&offset.to_be_bytes());
+            hvp.extend_from_slice(&blob);
+        }
+        hvp
+    }
+}
+
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+// A fresh directory under the system temp dir for the test `name`.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hvp-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn write(dir: &Path, path: &str, data: &[u8]) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, data).unwrap();
+}
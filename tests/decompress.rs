@@ -0,0 +1,59 @@
+mod common;
+
+use std::io::Cursor;
+use hvp::{HvpArchive, HvpEntry, HvpError};
+
+use common::{zlib, ArchiveBuilder};
+
+// An archive holding a single compressed file `name` with `data`, recording
+// `size` as its uncompressed size.
+fn archive(name: &str, data: &[u8], size: u32) -> Vec<u8> {
+    let blob = zlib(data);
+    ArchiveBuilder::new(1).file_with(name, 1, blob.len() as u32, size, &blob).build()
+}
+
+fn only_file(hvp: &HvpArchive<Cursor<Vec<u8>>>) -> hvp::FileEntry {
+    match &hvp.entries()[0] {
+        HvpEntry::File(file) => file.clone(),
+        HvpEntry::Directory(_) => panic!("expected a file"),
+    }
+}
+
+#[test]
+fn compressed_entry_is_not_zero_prefixed() {
+    let data = b"hello hello hello hello hello";
+    let hvp = HvpArchive::new(Cursor::new(archive("a.txt", data, data.len() as u32))).unwrap();
+    assert_eq!(hvp.read(&only_file(&hvp)).unwrap(), data);
+}
+
+#[test]
+fn compressed_entry_with_wrong_size_is_an_error() {
+    let data = b"hello hello hello hello hello";
+    let hvp = HvpArchive::new(Cursor::new(archive("a.txt", data, 100))).unwrap();
+    match hvp.read(&only_file(&hvp)) {
+        Err(HvpError::SizeMismatch { entry, expected, actual }) => {
+            assert_eq!(entry, "a.txt");
+            assert_eq!(expected, 100);
+            assert_eq!(actual, data.len() as u64);
+        }
+        other => panic!("expected a size mismatch, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn compressed_entry_longer_than_recorded_is_an_error() {
+    let data = b"hello hello hello hello hello";
+    let hvp = HvpArchive::new(Cursor::new(archive("a.txt", data, 4))).unwrap();
+    let file = only_file(&hvp);
+    match hvp.read(&file) {
+        Err(HvpError::SizeMismatch { expected, actual, .. }) => {
+            assert_eq!(expected, 4);
+            assert!(actual > 4);
+        }
+        other => panic!("expected a size mismatch, got {:?}", other.map(|_| ())),
+    }
+    match hvp.verify_file(&file) {
+        Err(HvpError::SizeMismatch { expected, actual, .. }) => assert!(expected == 4 && actual > 4),
+        other => panic!("expected a size mismatch, got {:?}", other),
+    }
+}
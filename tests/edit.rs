@@ -0,0 +1,128 @@
+mod common;
+
+use std::io::Cursor;
+use hvp::{HvpArchive, HvpError};
+
+use common::ArchiveBuilder;
+
+// An archive holding a single stored file `name` with `data`.
+fn archive(name: &str, data: &[u8]) -> Vec<u8> {
+    ArchiveBuilder::new(1).file(name, data).build()
+}
+
+#[test]
+fn add_keeps_data_when_the_table_outgrows_the_archive() {
+    let mut bytes = archive("a.txt", b"xy");
+    let original_len = bytes.len();
+    {
+        let mut hvp = HvpArchive::new(Cursor::new(&mut bytes)).unwrap();
+        hvp.add("dir/b.txt", b"hello", false).unwrap();
+        hvp.add("dir/c.txt", b"world", false).unwrap();
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    assert!(hvp.size() > original_len as u64);
+    let files: Vec<_> = hvp.files().map(|file| (file.path.clone(), hvp.read(file).unwrap())).collect();
+    assert_eq!(
+        files,
+        [
+            ("a.txt".to_string(), b"xy".to_vec()),
+            ("dir/b.txt".to_string(), b"hello".to_vec()),
+            ("dir/c.txt".to_string(), b"world".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn replace_writes_in_place_only_what_fits() {
+    let mut bytes = archive("a.txt", b"hello");
+    let len = bytes.len();
+    {
+        let mut hvp = HvpArchive::new(Cursor::new(&mut bytes)).unwrap();
+        hvp.replace("a.txt", b"hi").unwrap();
+        hvp.replace("a.txt", b"goodbye").unwrap();
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    let file = hvp.find("a.txt").unwrap();
+    assert_eq!(hvp.read(file).unwrap(), b"goodbye");
+    assert_eq!(u64::from(file.offset), len as u64);
+    assert_eq!(hvp.size(), len as u64 + 7);
+}
+
+#[test]
+fn replace_keeps_data_shared_with_other_files() {
+    let data = b"shared shared shared shared shared";
+    let mut bytes = ArchiveBuilder::new(2).compressed("a.txt", data).compressed("b.txt", data).build();
+    // Point `b.txt` at the data of `a.txt`.
+    let (a, b_record) = {
+        let hvp = HvpArchive::new(Cursor::new(bytes.clone())).unwrap();
+        (hvp.find("a.txt").unwrap().offset, hvp.record_offset("b.txt").unwrap() as usize)
+    };
+    bytes[b_record + 21..b_record + 25].copy_from_slice(&a.to_be_bytes());
+    {
+        let mut hvp = HvpArchive::new(Cursor::new(&mut bytes)).unwrap();
+        hvp.replace("a.txt", b"bbbb").unwrap();
+        assert_ne!(hvp.find("a.txt").unwrap().offset, a);
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(hvp.read(hvp.find("a.txt").unwrap()).unwrap(), b"bbbb");
+    assert_eq!(hvp.read(hvp.find("b.txt").unwrap()).unwrap(), data);
+}
+
+#[test]
+fn compact_keeps_stored_data_whatever_its_comp_size() {
+    let mut bytes = archive("a.txt", b"hello");
+    // The compressed size of a stored file is not used for its data.
+    bytes[49..53].copy_from_slice(&2u32.to_be_bytes());
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    let mut out = Cursor::new(Vec::new());
+    hvp.compact_to(&mut out).unwrap();
+
+    let hvp = HvpArchive::new(Cursor::new(out.into_inner())).unwrap();
+    let file = hvp.find("a.txt").unwrap();
+    assert_eq!(hvp.read(file).unwrap(), b"hello");
+    assert_eq!(hvp.size(), u64::from(file.offset) + 5);
+}
+
+#[test]
+fn edits_look_up_paths_like_get() {
+    let mut bytes = archive("a.txt", b"xy");
+    {
+        let mut hvp = HvpArchive::new(Cursor::new(&mut bytes)).unwrap();
+        hvp.add("dir\\b.txt", b"hello", false).unwrap();
+        hvp.add("dir//c.txt", b"world", false).unwrap();
+        hvp.replace("./dir\\b.txt", b"hi").unwrap();
+        hvp.remove("dir/./c.txt").unwrap();
+        assert!(matches!(hvp.add("./a.txt", b"", false), Err(HvpError::Exists { .. })));
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    let files: Vec<_> = hvp.files().map(|file| (file.path.clone(), hvp.read(file).unwrap())).collect();
+    assert_eq!(files, [("a.txt".to_string(), b"xy".to_vec()), ("dir/b.txt".to_string(), b"hi".to_vec())]);
+}
+
+#[test]
+fn add_rejects_unsafe_names() {
+    let mut hvp = HvpArchive::new(Cursor::new(archive("a.txt", b"xy"))).unwrap();
+    for path in ["../b.txt", "dir/../../b.txt", "c:b.txt", "", "/"] {
+        assert!(matches!(hvp.add(path, b"", false), Err(HvpError::UnsafeName { .. })), "{:?}", path);
+    }
+    assert_eq!(hvp.files().count(), 1);
+}
+
+#[test]
+fn add_rejects_paths_too_deep_to_reopen() {
+    let mut bytes = archive("a.txt", b"xy");
+    {
+        let mut hvp = HvpArchive::new(Cursor::new(&mut bytes)).unwrap();
+        let path = format!("{}b.txt", "d/".repeat(257));
+        assert!(matches!(hvp.add(&path, b"", false), Err(HvpError::TooDeep { limit: 256, .. })));
+        hvp.add(&format!("{}b.txt", "d/".repeat(256)), b"deep", false).unwrap();
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    let file = hvp.files().last().unwrap();
+    assert_eq!(hvp.read(file).unwrap(), b"deep");
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::{
+    fs, io::Cursor, path::Path, process::{Command, Output}
+};
+use hvp::HvpArchive;
+
+use common::{temp_dir, ArchiveBuilder};
+
+// An archive with entries named `..`, `a/b` and `absolute` next to a safe one.
+fn archive(absolute: &str) -> Vec<u8> {
+    ArchiveBuilder::new(4)
+        .dir("..", 1)
+        .file("up.txt", b"up")
+        .dir("a/b", 1)
+        .file("c.txt", b"c")
+        .file(absolute, b"absolute")
+        .file("ok.txt", b"ok")
+        .build()
+}
+
+fn unpack(dir: &Path, bytes: &[u8], flags: &[&str]) -> Output {
+    let (path, out) = (dir.join("archive.hvp"), dir.join("out"));
+    fs::write(&path, bytes).unwrap();
+    fs::create_dir_all(&out).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_hvpextract")).arg("unpack").args(flags).arg(&path).arg(&out).output().unwrap()
+}
+
+#[test]
+fn unsafe_names_are_refused() {
+    let dir = temp_dir("refuse");
+    let bytes = archive("/etc");
+    let output = unpack(&dir, &bytes, &[]);
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    for path in ["..", "a/b", "/etc"] {
+        let refusal = format!("Refusing unsafe path {} (record at offset {:#x})", path, hvp.record_offset(path).unwrap());
+        assert!(stdout.contains(&refusal), "{:?} not in {}", refusal, stdout);
+    }
+    assert!(stdout.contains("Skipped 3 entries with unsafe names"));
+    assert_eq!(fs::read(dir.join("out/ok.txt")).unwrap(), b"ok");
+    assert!(!dir.join("up.txt").exists());
+    assert!(!dir.join("out/a").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unsafe_names_are_extracted_when_allowed() {
+    let dir = temp_dir("allow");
+    // An absolute name inside the test directory rather than `/etc`, the
+    // file really gets written there.
+    let absolute = dir.join("absolute.txt");
+    let output = unpack(&dir, &archive(absolute.to_str().unwrap()), &["--allow-unsafe-paths"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    assert_eq!(fs::read(dir.join("up.txt")).unwrap(), b"up");
+    assert_eq!(fs::read(dir.join("out/a/b/c.txt")).unwrap(), b"c");
+    assert_eq!(fs::read(&absolute).unwrap(), b"absolute");
+    assert_eq!(fs::read(dir.join("out/ok.txt")).unwrap(), b"ok");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::io::Cursor;
+use hvp::{HvpArchive, HvpEntry};
+
+use common::ArchiveBuilder;
+
+fn archive() -> HvpArchive<Cursor<Vec<u8>>> {
+    let bytes = ArchiveBuilder::new(5)
+        .file("Readme.txt", b"1")
+        .file("README.txt", b"2")
+        .file("dup", b"3")
+        .file("dup", b"4")
+        .dir("Dir", 1)
+        .file("x", b"5")
+        .build();
+    HvpArchive::new(Cursor::new(bytes)).unwrap()
+}
+
+fn data(hvp: &HvpArchive<Cursor<Vec<u8>>>, entry: Option<&HvpEntry>) -> Vec<u8> {
+    match entry {
+        Some(HvpEntry::File(file)) => hvp.read(file).unwrap(),
+        other => panic!("expected a file, got {:?}", other.map(HvpEntry::path)),
+    }
+}
+
+#[test]
+fn get_matches_normalised_paths_exactly() {
+    let hvp = archive();
+    assert_eq!(data(&hvp, hvp.get("Readme.txt")), b"1");
+    assert_eq!(data(&hvp, hvp.get("Dir\\x")), b"5");
+    assert_eq!(data(&hvp, hvp.get("./Dir//x")), b"5");
+    assert_eq!(data(&hvp, hvp.get("dup")), b"3");
+    assert_eq!(hvp.get("Dir").map(HvpEntry::path), Some("Dir"));
+    assert!(hvp.get("readme.txt").is_none());
+    assert!(hvp.get("dir/x").is_none());
+}
+
+#[test]
+fn get_ci_prefers_an_exact_match_over_the_first_one() {
+    let hvp = archive();
+    assert_eq!(data(&hvp, hvp.get_ci("README.txt")), b"2");
+    assert_eq!(data(&hvp, hvp.get_ci("readme.txt")), b"1");
+    assert_eq!(data(&hvp, hvp.get_ci("README.TXT")), b"1");
+    assert_eq!(data(&hvp, hvp.get_ci("dir/X")), b"5");
+    assert!(hvp.get_ci("missing").is_none());
+}
+
+#[test]
+fn case_collisions_leave_out_duplicates() {
+    let hvp = archive();
+    let collisions: Vec<Vec<&str>> =
+        hvp.case_collisions().iter().map(|group| group.iter().map(|entry| entry.path()).collect()).collect();
+    assert_eq!(collisions, [["Readme.txt", "README.txt"]]);
+}
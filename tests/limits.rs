@@ -0,0 +1,62 @@
+mod common;
+
+use std::io::Cursor;
+use hvp::{HvpArchive, HvpError, ParseOptions};
+
+use common::{temp_dir, ArchiveBuilder};
+
+fn archive() -> Vec<u8> {
+    ArchiveBuilder::new(3).file("a.txt", b"hello").compressed("b.txt", b"world").file("c.txt", b"abc").build()
+}
+
+fn options(max_entry_size: u64, max_total_size: u64) -> ParseOptions {
+    ParseOptions { max_entry_size, max_total_size, ..ParseOptions::default() }
+}
+
+#[test]
+fn entries_over_the_entry_limit_are_not_read() {
+    let hvp = HvpArchive::new_with(Cursor::new(archive()), &options(4, u64::MAX)).unwrap();
+    for path in ["a.txt", "b.txt"] {
+        let mut out = Vec::new();
+        match hvp.read_to(hvp.find(path).unwrap(), &mut out) {
+            Err(HvpError::EntryTooLarge { entry, limit }) => assert_eq!((entry.as_str(), limit), (path, 4)),
+            other => panic!("expected an entry too large, got {:?}", other),
+        }
+        assert!(out.is_empty());
+    }
+    assert_eq!(hvp.read(hvp.find("c.txt").unwrap()).unwrap(), b"abc");
+}
+
+#[test]
+fn entries_over_the_total_limit_are_not_read() {
+    let hvp = HvpArchive::new_with(Cursor::new(archive()), &options(u64::MAX, 8)).unwrap();
+    assert_eq!(hvp.read(hvp.find("a.txt").unwrap()).unwrap(), b"hello");
+    match hvp.read(hvp.find("b.txt").unwrap()) {
+        Err(HvpError::TotalTooLarge { limit }) => assert_eq!(limit, 8),
+        other => panic!("expected a total too large, got {:?}", other),
+    }
+    // The rejected entry does not count against the total.
+    assert_eq!(hvp.read(hvp.find("c.txt").unwrap()).unwrap(), b"abc");
+}
+
+#[test]
+fn shared_readers_keep_the_limits() {
+    let dir = temp_dir("limits");
+    let path = dir.join("archive.hvp");
+    std::fs::write(&path, archive()).unwrap();
+
+    let hvp = HvpArchive::open_with(&path, &options(4, u64::MAX)).unwrap();
+    let reader = hvp.shared_reader().unwrap();
+    assert!(matches!(reader.read(hvp.find("b.txt").unwrap()), Err(HvpError::EntryTooLarge { .. })));
+    assert_eq!(reader.read(hvp.find("c.txt").unwrap()).unwrap(), b"abc");
+
+    let hvp = HvpArchive::open_with(&path, &options(u64::MAX, 8)).unwrap();
+    assert_eq!(hvp.read(hvp.find("a.txt").unwrap()).unwrap(), b"hello");
+    // The shared reader starts from what the archive has read so far.
+    let reader = hvp.shared_reader().unwrap();
+    assert!(matches!(reader.read(hvp.find("b.txt").unwrap()), Err(HvpError::TotalTooLarge { limit: 8 })));
+    assert_eq!(reader.read(hvp.find("c.txt").unwrap()).unwrap(), b"abc");
+    assert!(matches!(reader.read(hvp.find("c.txt").unwrap()), Err(HvpError::TotalTooLarge { limit: 8 })));
+    drop(hvp);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use std::{fs, io::Cursor};
+use hvp::{HvpArchive, NameEncoding};
+
+use common::{temp_dir, write};
+
+#[test]
+fn manifest_rebuilds_the_archive_byte_for_byte() {
+    let dir = temp_dir("manifest");
+    let src = dir.join("src");
+    write(&src, "readme.txt", b"hello hello hello hello hello hello");
+    write(&src, "levels/level1/map.bin", &(0..=255).collect::<Vec<u8>>());
+    let mut packed = Cursor::new(Vec::new());
+    hvp::pack_dir(&src, &mut packed, true, NameEncoding::Utf8).unwrap();
+    let mut original = packed.into_inner();
+    // Unknown header bytes and trailing padding have to survive as well.
+    original[11..16].copy_from_slice(b"\x01\x02\x03\x04\x05");
+    original[20..40].copy_from_slice(&[0xAA; 20]);
+    original.extend_from_slice(&[0x55; 13]);
+    // As do entry types and compression flags other than 1.
+    let records: Vec<_> = {
+        let hvp = HvpArchive::new(Cursor::new(original.clone())).unwrap();
+        hvp.files().map(|file| hvp.record_offset(&file.path).unwrap() as usize).collect()
+    };
+    for record in records {
+        original[record + 4] = 7;
+        if original[record + 5..record + 9] != [0; 4] {
+            original[record + 5..record + 9].copy_from_slice(&0x100u32.to_be_bytes());
+        }
+    }
+
+    let hvp = HvpArchive::new(Cursor::new(original.clone())).unwrap();
+    let manifest = dir.join("archive.manifest");
+    hvp::write_manifest(&hvp, &manifest).unwrap();
+    let mut rebuilt = Cursor::new(Vec::new());
+    hvp::pack_manifest(&manifest, &src, &mut rebuilt).unwrap();
+    assert_eq!(rebuilt.into_inner(), original);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::{
+    fs, io::{Cursor, Read, Seek}
+};
+use hvp::{HvpArchive, HvpEntry, HvpError, ParseOptions};
+
+use common::{temp_dir, ArchiveBuilder};
+
+fn archive() -> Vec<u8> {
+    ArchiveBuilder::new(2)
+        .dir("dir", 2)
+        .file("stored.txt", b"stored")
+        .compressed("packed.txt", b"packed packed packed packed")
+        .file("top.txt", b"top")
+        .build()
+}
+
+fn paths<R: Read + Seek>(hvp: &HvpArchive<R>) -> Vec<&str> {
+    hvp.walk().map(HvpEntry::path).collect()
+}
+
+#[test]
+fn slices_parse_like_readers() {
+    let bytes = archive();
+    let hvp = HvpArchive::from_slice(&bytes, &ParseOptions::default()).unwrap();
+    let expected = HvpArchive::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(paths(&hvp), paths(&expected));
+    assert_eq!(hvp.size(), expected.size());
+    for (file, other) in hvp.files().zip(expected.files()) {
+        assert_eq!((file.raw_name.as_slice(), file.offset, file.comp_size), (other.raw_name.as_slice(), other.offset, other.comp_size));
+        assert_eq!(hvp.read(file).unwrap(), expected.read(other).unwrap());
+    }
+}
+
+#[test]
+fn slices_fail_like_readers() {
+    let bytes = archive();
+    for len in [5, 30, 45, 60, 100, 170] {
+        let slice = HvpArchive::from_slice(&bytes[..len], &ParseOptions::default()).err().unwrap();
+        let reader = HvpArchive::new(Cursor::new(&bytes[..len])).err().unwrap();
+        assert_eq!(slice.to_string(), reader.to_string(), "{}", len);
+    }
+    assert!(matches!(HvpArchive::from_slice(b"HV PackFile", &ParseOptions::default()), Err(HvpError::Truncated { offset: 11 })));
+}
+
+#[test]
+fn blobs_point_into_the_slice() {
+    let bytes = archive();
+    let hvp = HvpArchive::from_slice(&bytes, &ParseOptions::default()).unwrap();
+    for file in hvp.files() {
+        let blob = hvp.blob(file).unwrap();
+        assert!(bytes.as_ptr_range().contains(&blob.as_ptr()));
+        assert_eq!(blob, hvp.read_raw(file).unwrap());
+    }
+    assert_eq!(hvp.blob(hvp.find("dir/stored.txt").unwrap()).unwrap(), b"stored");
+
+    let mut file = hvp.find("top.txt").unwrap().clone();
+    file.offset = bytes.len() as u32 - 1;
+    assert!(matches!(hvp.blob(&file), Err(HvpError::OutOfBounds { .. })));
+}
+
+#[test]
+fn mapped_files_parse() {
+    let dir = temp_dir("mapped");
+    let path = dir.join("archive.hvp");
+    fs::write(&path, archive()).unwrap();
+    {
+        let map = hvp::map_file(&path).unwrap();
+        let hvp = HvpArchive::from_slice(&map, &ParseOptions::default()).unwrap();
+        assert_eq!(hvp.read(hvp.find("dir/packed.txt").unwrap()).unwrap(), b"packed packed packed packed");
+        assert_eq!(hvp.blob(hvp.find("top.txt").unwrap()).unwrap(), b"top");
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,8 @@
+use hvp::NameEncoding;
+
+#[test]
+fn auto_falls_back_to_latin1_per_byte() {
+    assert_eq!(NameEncoding::Auto.decode("caf\u{e9}".as_bytes()).unwrap(), "caf\u{e9}");
+    assert_eq!(NameEncoding::Auto.decode(&[0x80, 0x81]).unwrap(), "\u{20ac}\u{81}");
+    assert_eq!(NameEncoding::Auto.decode(b"a\xe9\x9d").unwrap(), "a\u{e9}\u{9d}");
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use std::{fs, io::Cursor};
+use hvp::{HvpArchive, NameEncoding};
+
+use common::{temp_dir, write};
+
+#[test]
+fn packed_directory_reads_back_identically() {
+    let dir = temp_dir("pack");
+    write(&dir, "readme.txt", b"hello hello hello hello hello hello");
+    write(&dir, "levels/level1/map.bin", &(0..=255).collect::<Vec<u8>>());
+    write(&dir, "levels/level1/empty", b"");
+    write(&dir, "sounds/a.wav", &[7; 1000]);
+
+    for compress in [true, false] {
+        let mut out = Cursor::new(Vec::new());
+        hvp::pack_dir(&dir, &mut out, compress, NameEncoding::Utf8).unwrap();
+        let hvp = HvpArchive::new(Cursor::new(out.into_inner())).unwrap();
+
+        let mut paths: Vec<_> = hvp.files().map(|file| file.path.clone()).collect();
+        paths.sort();
+        assert_eq!(paths, ["levels/level1/empty", "levels/level1/map.bin", "readme.txt", "sounds/a.wav"]);
+        for file in hvp.files() {
+            assert_eq!(hvp.read(file).unwrap(), fs::read(dir.join(&file.path)).unwrap(), "{}", file.path);
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use hvp::{HvpArchive, HvpError, ParseOptions};
+
+use common::ArchiveBuilder;
+
+fn data() -> Vec<u8> {
+    (0..1000u32).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+fn archive(options: &ParseOptions) -> HvpArchive<Cursor<Vec<u8>>> {
+    let bytes = ArchiveBuilder::new(2).compressed("packed.bin", &data()).file("stored.bin", &data()).build();
+    HvpArchive::new_with(Cursor::new(bytes), options).unwrap()
+}
+
+fn read_n<R: Read>(reader: &mut R, n: usize) -> Vec<u8> {
+    let mut buf = vec![0; n];
+    reader.read_exact(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn entries_read_whole() {
+    let hvp = archive(&ParseOptions::default());
+    for path in ["packed.bin", "stored.bin"] {
+        let mut out = Vec::new();
+        hvp.open_entry(path).unwrap().read_to_end(&mut out).unwrap();
+        assert_eq!(out, data(), "{}", path);
+    }
+}
+
+#[test]
+fn seeks_forward_and_back() {
+    let hvp = archive(&ParseOptions::default());
+    let data = data();
+    for path in ["packed.bin", "stored.bin"] {
+        let mut reader = hvp.open_entry(path).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(500)).unwrap(), 500);
+        assert_eq!(read_n(&mut reader, 10), &data[500..510], "{}", path);
+        assert_eq!(reader.seek(SeekFrom::Current(90)).unwrap(), 600);
+        assert_eq!(read_n(&mut reader, 10), &data[600..610], "{}", path);
+        // Going back restarts inflating from the start of the entry.
+        assert_eq!(reader.seek(SeekFrom::Start(100)).unwrap(), 100);
+        assert_eq!(read_n(&mut reader, 10), &data[100..110], "{}", path);
+        assert_eq!(reader.seek(SeekFrom::Current(-20)).unwrap(), 90);
+        assert_eq!(read_n(&mut reader, 10), &data[90..100], "{}", path);
+    }
+}
+
+#[test]
+fn seeks_from_the_end() {
+    let hvp = archive(&ParseOptions::default());
+    for path in ["packed.bin", "stored.bin"] {
+        let mut reader = hvp.open_entry(path).unwrap();
+        assert_eq!(reader.seek(SeekFrom::End(-10)).unwrap(), 990);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, &data()[990..], "{}", path);
+        assert!(reader.seek(SeekFrom::End(-1001)).is_err());
+    }
+}
+
+#[test]
+fn reads_past_the_end_are_empty() {
+    let hvp = archive(&ParseOptions::default());
+    for path in ["packed.bin", "stored.bin"] {
+        let mut reader = hvp.open_entry(path).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(2000)).unwrap(), 2000);
+        assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0, "{}", path);
+        reader.seek(SeekFrom::Start(995)).unwrap();
+        assert_eq!(reader.read(&mut [0; 16]).unwrap(), 5, "{}", path);
+    }
+}
+
+#[test]
+fn readers_keep_the_limits() {
+    let hvp = archive(&ParseOptions { max_entry_size: 999, ..ParseOptions::default() });
+    assert!(matches!(hvp.open_entry("packed.bin"), Err(HvpError::EntryTooLarge { limit: 999, .. })));
+
+    let hvp = archive(&ParseOptions { max_total_size: 1500, ..ParseOptions::default() });
+    hvp.open_entry("packed.bin").unwrap();
+    assert!(matches!(hvp.open_entry("stored.bin"), Err(HvpError::TotalTooLarge { limit: 1500 })));
+    assert!(matches!(hvp.read(hvp.find("stored.bin").unwrap()), Err(HvpError::TotalTooLarge { limit: 1500 })));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::{fs, io::Cursor, process::Command};
+use hvp::HvpArchive;
+
+use common::{temp_dir, zlib, ArchiveBuilder};
+
+#[test]
+fn salvage_recovers_named_and_scanned_files_once() {
+    let dir = temp_dir("salvage");
+    // `c.txt` holds a zlib stream of its own, which must not be recovered
+    // again on top of `c.txt`.
+    let inner = zlib(b"inner inner inner inner");
+    let mut bytes = ArchiveBuilder::new(3)
+        .compressed("a.txt", b"aaaa aaaa aaaa aaaa")
+        .compressed("b.txt", b"bbbb bbbb bbbb bbbb")
+        .compressed("c.txt", &inner)
+        .build();
+    let (offsets, b_record) = {
+        let hvp = HvpArchive::new(Cursor::new(bytes.clone())).unwrap();
+        let offsets: Vec<u32> = hvp.files().map(|file| file.offset).collect();
+        (offsets, hvp.record_offset("b.txt").unwrap() as usize)
+    };
+    // Break the name length of `b.txt`, the records after it are lost too.
+    bytes[b_record + 25..b_record + 29].copy_from_slice(&u32::MAX.to_be_bytes());
+    let path = dir.join("archive.hvp");
+    fs::write(&path, &bytes).unwrap();
+    let out = dir.join("out");
+    fs::create_dir_all(&out).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hvpextract")).arg("salvage").arg(&path).arg(&out).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let mut names: Vec<_> = fs::read_dir(&out).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+    names.sort();
+    let (b, c) = (format!("recovered_{}.bin", offsets[1]), format!("recovered_{}.bin", offsets[2]));
+    assert_eq!(names, ["a.txt", b.as_str(), c.as_str(), "salvage_report.txt"]);
+    assert_eq!(fs::read(out.join("a.txt")).unwrap(), b"aaaa aaaa aaaa aaaa");
+    assert_eq!(fs::read(out.join(&b)).unwrap(), b"bbbb bbbb bbbb bbbb");
+    assert_eq!(fs::read(out.join(&c)).unwrap(), inner);
+    let report = fs::read_to_string(out.join("salvage_report.txt")).unwrap();
+    assert!(report.ends_with("1 files recovered by their records, 2 by scanning\n"), "{}", report);
+    fs::remove_dir_all(&dir).unwrap();
+}
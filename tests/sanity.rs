@@ -0,0 +1,76 @@
+mod common;
+
+use std::io::Cursor;
+use hvp::{HvpArchive, HvpError};
+
+use common::ArchiveBuilder;
+
+// The offset of the first entry record, right after the header.
+const RECORD: u64 = 40;
+// Where the name length of a file record starts.
+const NAME_LENGTH: usize = RECORD as usize + 5 + 20;
+
+fn bad_record(bytes: Vec<u8>) -> (u64, String) {
+    match HvpArchive::new(Cursor::new(bytes)) {
+        Err(HvpError::BadRecord { offset, reason }) => (offset, reason),
+        Err(e) => panic!("expected a bad record, got {}", e),
+        Ok(_) => panic!("expected a bad record, got an archive"),
+    }
+}
+
+#[test]
+fn overlong_name_is_a_bad_record() {
+    let mut bytes = ArchiveBuilder::new(1).file("a.txt", &[0; 2000]).build();
+    bytes[NAME_LENGTH..NAME_LENGTH + 4].copy_from_slice(&1025u32.to_be_bytes());
+    assert_eq!(bad_record(bytes), (RECORD, "name length 1025 exceeds the limit of 1024".to_string()));
+}
+
+#[test]
+fn name_past_the_end_is_a_bad_record() {
+    let mut bytes = ArchiveBuilder::new(1).file("a.txt", b"").build();
+    bytes[NAME_LENGTH..NAME_LENGTH + 4].copy_from_slice(&6u32.to_be_bytes());
+    assert_eq!(bad_record(bytes), (RECORD, "name length 6 runs past the end of the archive".to_string()));
+}
+
+#[test]
+fn implausible_entry_count_is_a_bad_record() {
+    let bytes = ArchiveBuilder::new(1).dir("d", 1000).file("a.txt", b"").build();
+    let left = bytes.len() as u64 - (RECORD + 17 + 1);
+    assert_eq!(bad_record(bytes), (RECORD, format!("1000 entries cannot fit into the {} bytes left in the archive", left)));
+}
+
+#[test]
+fn data_past_the_end_is_out_of_bounds() {
+    let mut bytes = ArchiveBuilder::new(1).file("a.txt", b"hello").build();
+    bytes.truncate(bytes.len() - 1);
+    let hvp = HvpArchive::new(Cursor::new(bytes)).unwrap();
+    let file = hvp.find("a.txt").unwrap();
+    for result in [hvp.read(file).map(drop), hvp.read_raw(file).map(drop), hvp.verify_file(file)] {
+        match result {
+            Err(HvpError::OutOfBounds { entry, offset, len }) => {
+                assert_eq!((entry.as_str(), offset, len), ("a.txt", u64::from(file.offset), 5));
+            }
+            other => panic!("expected out of bounds, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn deep_nesting_is_a_bad_record() {
+    let mut builder = ArchiveBuilder::new(1);
+    for _ in 0..300 {
+        builder = builder.dir("d", 1);
+    }
+    let bytes = builder.file("a.txt", b"").build();
+    // Every directory record named `d` takes 18 bytes.
+    let offset = RECORD + 256 * 18;
+    assert_eq!(bad_record(bytes), (offset, "directories are nested deeper than 256 levels".to_string()));
+}
+
+#[test]
+fn implausible_header_count_is_a_bad_record() {
+    let mut bytes = ArchiveBuilder::new(1).file("a.txt", b"").build();
+    bytes[16..20].copy_from_slice(&1_000_000u32.to_be_bytes());
+    let left = bytes.len() as u64 - RECORD;
+    assert_eq!(bad_record(bytes), (16, format!("1000000 entries cannot fit into the {} bytes left in the archive", left)));
+}